@@ -0,0 +1,123 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::io;
+use std::io::prelude::*;
+
+/// Packs bits into bytes, most significant bit first
+pub struct BitWriter<W: Write> {
+    output: W,
+    buffer: u8,
+    buffered_bits: u8,
+}
+
+impl<W: Write> BitWriter<W> {
+    pub fn new(output: W) -> BitWriter<W> {
+        BitWriter { output, buffer: 0, buffered_bits: 0 }
+    }
+
+    pub fn write_bit(&mut self, bit: bool) -> io::Result<()> {
+        self.buffer |= (bit as u8) << (7 - self.buffered_bits);
+        self.buffered_bits += 1;
+        if self.buffered_bits == 8 {
+            self.output.write_all(&[self.buffer])?;
+            self.buffer = 0;
+            self.buffered_bits = 0;
+        }
+        Ok(())
+    }
+
+    pub fn write_bits(&mut self, value: u64, bits_count: u8)
+                      -> io::Result<()> {
+        assert!(bits_count <= 64);
+        for bit_index in (0..bits_count).rev() {
+            self.write_bit(((value >> bit_index) & 1) == 1)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_byte(&mut self, byte: u8) -> io::Result<()> {
+        if self.buffered_bits == 0 {
+            self.output.write_all(&[byte])
+        } else {
+            self.write_bits(byte as u64, 8)
+        }
+    }
+
+    /** Pads last partial byte with zeros and returns the underlying writer */
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.buffered_bits > 0 {
+            self.output.write_all(&[self.buffer])?;
+        }
+        self.output.flush()?;
+        Ok(self.output)
+    }
+}
+
+/// Unpacks bits from bytes, most significant bit first
+pub struct BitReader<R: Read> {
+    input: R,
+    buffer: u8,
+    buffered_bits: u8,
+}
+
+impl<R: Read> BitReader<R> {
+    pub fn new(input: R) -> BitReader<R> {
+        BitReader { input, buffer: 0, buffered_bits: 0 }
+    }
+
+    pub fn read_bit(&mut self) -> io::Result<bool> {
+        if self.buffered_bits == 0 {
+            self.buffer = self.read_raw_byte()?;
+            self.buffered_bits = 8;
+        }
+        self.buffered_bits -= 1;
+        Ok(((self.buffer >> self.buffered_bits) & 1) == 1)
+    }
+
+    pub fn read_bits(&mut self, bits_count: u8) -> io::Result<u64> {
+        assert!(bits_count <= 64);
+        let mut value = 0u64;
+        for _ in 0..bits_count {
+            value = (value << 1) | self.read_bit()? as u64;
+        }
+        Ok(value)
+    }
+
+    pub fn read_byte(&mut self) -> io::Result<u8> {
+        if self.buffered_bits == 0 {
+            self.read_raw_byte()
+        } else {
+            self.read_bits(8).map(|value| value as u8)
+        }
+    }
+
+    /** Drops remaining bits of current partial byte */
+    pub fn align_to_byte(&mut self) {
+        self.buffered_bits = 0;
+    }
+
+    pub fn into_inner(self) -> R {
+        self.input
+    }
+
+    fn read_raw_byte(&mut self) -> io::Result<u8> {
+        let mut byte = [0u8];
+        self.input.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+}
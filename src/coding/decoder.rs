@@ -0,0 +1,69 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::io;
+use std::io::prelude::*;
+
+use coding::{FinalProbability, RANGE_BOTTOM, split_point};
+
+/// Reverses Encoder given the same sequence of probabilities
+pub struct Decoder<R: Read> {
+    input: R,
+    code: u32,
+    range: u32,
+}
+
+impl<R: Read> Decoder<R> {
+    pub fn new(input: R) -> io::Result<Decoder<R>> {
+        let mut decoder = Decoder { input, code: 0, range: u32::MAX };
+        if decoder.read_byte()? != 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                      "coded stream must start with zero"));
+        }
+        for _ in 0..4 {
+            decoder.code = (decoder.code << 8) | decoder.read_byte()? as u32;
+        }
+        Ok(decoder)
+    }
+
+    pub fn decode_bit(&mut self, probability: FinalProbability)
+                      -> io::Result<bool> {
+        let bound = split_point(self.range, probability);
+        let bit = self.code >= bound;
+        if bit {
+            self.code -= bound;
+            self.range -= bound;
+        } else {
+            self.range = bound;
+        }
+        while self.range < RANGE_BOTTOM {
+            self.range <<= 8;
+            self.code = (self.code << 8) | self.read_byte()? as u32;
+        }
+        Ok(bit)
+    }
+
+    pub fn into_inner(self) -> R {
+        self.input
+    }
+
+    fn read_byte(&mut self) -> io::Result<u8> {
+        let mut byte = [0u8];
+        self.input.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+}
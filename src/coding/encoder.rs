@@ -0,0 +1,83 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use std::io;
+use std::io::prelude::*;
+
+use coding::{FinalProbability, RANGE_BOTTOM, split_point};
+
+/// Binary range coder with carry propagation, byte oriented renormalization
+pub struct Encoder<W: Write> {
+    output: W,
+    low: u64,
+    range: u32,
+    cache: u8,
+    cache_size: u64,
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(output: W) -> Encoder<W> {
+        Encoder {
+            output,
+            low: 0,
+            range: u32::MAX,
+            cache: 0,
+            cache_size: 1,
+        }
+    }
+
+    pub fn encode_bit(&mut self, probability: FinalProbability, bit: bool)
+                      -> io::Result<()> {
+        let bound = split_point(self.range, probability);
+        if bit {
+            self.low += bound as u64;
+            self.range -= bound;
+        } else {
+            self.range = bound;
+        }
+        while self.range < RANGE_BOTTOM {
+            self.range <<= 8;
+            self.shift_low()?;
+        }
+        Ok(())
+    }
+
+    /** Writes out pending bytes and returns the underlying writer */
+    pub fn finish(mut self) -> io::Result<W> {
+        for _ in 0..5 {
+            self.shift_low()?;
+        }
+        self.output.flush()?;
+        Ok(self.output)
+    }
+
+    fn shift_low(&mut self) -> io::Result<()> {
+        if self.low < 0xFF00_0000 || self.low >= 1 << 32 {
+            let carry = (self.low >> 32) as u8;
+            let mut pending = self.cache;
+            while self.cache_size > 0 {
+                self.output.write_all(&[pending.wrapping_add(carry)])?;
+                pending = 0xFF;
+                self.cache_size -= 1;
+            }
+            self.cache = (self.low >> 24) as u8;
+        }
+        self.cache_size += 1;
+        self.low = (self.low & 0x00FF_FFFF) << 8;
+        Ok(())
+    }
+}
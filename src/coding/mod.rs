@@ -0,0 +1,168 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+pub mod bit_io;
+pub mod decoder;
+pub mod encoder;
+
+use std::io;
+use std::io::prelude::*;
+
+use ::{MAX_ORDER, MAX_WINDOW_SIZE};
+
+/// Probability of the next bit being zero, excluding both 0 and 1
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FinalProbability(u32);
+
+impl FinalProbability {
+    pub const FRACTIONAL_BITS: u8 = 23;
+    pub const ONE_RAW: u32 = 1 << FinalProbability::FRACTIONAL_BITS;
+    pub const HALF: FinalProbability =
+        FinalProbability(FinalProbability::ONE_RAW / 2);
+
+    pub fn new(raw: u32) -> FinalProbability {
+        assert!(raw > 0 && raw < FinalProbability::ONE_RAW,
+                "probability out of range: {}", raw);
+        FinalProbability(raw)
+    }
+
+    pub fn raw(self) -> u32 {
+        self.0
+    }
+
+    pub fn as_f64(self) -> f64 {
+        self.0 as f64 / FinalProbability::ONE_RAW as f64
+    }
+}
+
+/** Range coder renormalizes range to stay at or above that, so that
+ * neither subrange gets empty */
+const RANGE_BOTTOM: u32 = 1 << 24;
+
+/** Size of the subrange for zero bit, must be the same in both directions */
+fn split_point(range: u32, probability: FinalProbability) -> u32 {
+    ((range as u64 * probability.raw() as u64) >>
+        FinalProbability::FRACTIONAL_BITS) as u32
+}
+
+/// Stream parameters stored before coded data
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Header {
+    pub max_order: usize,
+    pub max_window_size: usize,
+    /** Decoder has to build its tree with the same threshold */
+    pub degenerate_switch_threshold: usize,
+    pub length: u64,
+}
+
+impl Header {
+    pub const MAGIC: [u8; 4] = *b"DMXR";
+    pub const VERSION: u8 = 1;
+
+    pub fn write<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        assert!(self.max_order <= MAX_ORDER);
+        assert!(self.max_window_size > 0 &&
+            self.max_window_size <= MAX_WINDOW_SIZE);
+        assert!(self.max_order < self.max_window_size);
+        assert!(self.degenerate_switch_threshold > 0);
+        output.write_all(&Header::MAGIC)?;
+        output.write_all(&[Header::VERSION, self.max_order as u8])?;
+        write_varint(output, self.max_window_size as u64)?;
+        write_varint(output, self.degenerate_switch_threshold as u64)?;
+        write_varint(output, self.length)
+    }
+
+    pub fn read<R: Read>(input: &mut R) -> io::Result<Header> {
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic)?;
+        if magic != Header::MAGIC {
+            return Err(invalid_data(format!("wrong magic: {:?}", magic)));
+        }
+        let mut version_and_order = [0u8; 2];
+        input.read_exact(&mut version_and_order)?;
+        let version = version_and_order[0];
+        if version != Header::VERSION {
+            return Err(invalid_data(format!(
+                "unsupported format version {}, expected {}",
+                version, Header::VERSION)));
+        }
+        let max_order = version_and_order[1] as usize;
+        if max_order > MAX_ORDER {
+            return Err(invalid_data(format!(
+                "max order {} exceeds {}", max_order, MAX_ORDER)));
+        }
+        let max_window_size = read_varint(input)?;
+        if max_window_size == 0 {
+            return Err(invalid_data("max window size is zero".to_string()));
+        }
+        if max_window_size > MAX_WINDOW_SIZE as u64 {
+            return Err(invalid_data(format!(
+                "max window size {} exceeds {}",
+                max_window_size, MAX_WINDOW_SIZE)));
+        }
+        if max_order as u64 >= max_window_size {
+            return Err(invalid_data(format!(
+                "max order {} is not below max window size {}",
+                max_order, max_window_size)));
+        }
+        let degenerate_switch_threshold = read_varint(input)?;
+        if degenerate_switch_threshold == 0 ||
+            degenerate_switch_threshold > max_window_size {
+            return Err(invalid_data(format!(
+                "degenerate switch threshold {} is not in 1..={}",
+                degenerate_switch_threshold, max_window_size)));
+        }
+        let length = read_varint(input)?;
+        Ok(Header {
+            max_order,
+            max_window_size: max_window_size as usize,
+            degenerate_switch_threshold: degenerate_switch_threshold as usize,
+            length,
+        })
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/** LEB128, 7 bits per byte starting from least significant ones */
+fn write_varint<W: Write>(output: &mut W, mut value: u64) -> io::Result<()> {
+    while value >= 0x80 {
+        output.write_all(&[(value as u8) | 0x80])?;
+        value >>= 7;
+    }
+    output.write_all(&[value as u8])
+}
+
+fn read_varint<R: Read>(input: &mut R) -> io::Result<u64> {
+    let mut value = 0u64;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8];
+        input.read_exact(&mut byte)?;
+        let payload = (byte[0] & 0x7F) as u64;
+        if (shift == 63 && payload > 1) || shift > 63 {
+            return Err(invalid_data("varint overflows 64 bits".to_string()));
+        }
+        value |= payload << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
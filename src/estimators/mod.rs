@@ -0,0 +1,68 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// Probability of bit one with 16 fractional bits, moving towards each
+/// observed bit by a fixed fraction of the distance
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SimpleCounter(u16);
+
+impl SimpleCounter {
+    pub const FRACTIONAL_BITS: u8 = 16;
+    pub const ADAPTATION_SHIFT: u8 = 4;
+    pub const HALF: SimpleCounter =
+        SimpleCounter(1 << (SimpleCounter::FRACTIONAL_BITS - 1));
+
+    pub fn new() -> SimpleCounter {
+        SimpleCounter::HALF
+    }
+
+    /** State after observing a run of identical bits, run length is capped
+     * as the counter saturates anyway */
+    pub fn after_run(run_length: usize, repeated_bit: bool) -> SimpleCounter {
+        let mut counter = SimpleCounter::new();
+        for _ in 0..run_length.min(255) {
+            counter.update(repeated_bit);
+        }
+        counter
+    }
+
+    /** Raw probability, stays strictly between 0 and 1 */
+    pub fn prediction(&self) -> u16 {
+        self.0
+    }
+
+    pub fn as_f64(&self) -> f64 {
+        self.0 as f64 / (1 << SimpleCounter::FRACTIONAL_BITS) as f64
+    }
+
+    pub fn update(&mut self, bit: bool) {
+        let shift = SimpleCounter::ADAPTATION_SHIFT;
+        if bit {
+            self.0 += (((1u32 << SimpleCounter::FRACTIONAL_BITS) -
+                self.0 as u32) >> shift) as u16;
+        } else {
+            self.0 -= self.0 >> shift;
+        }
+    }
+}
+
+impl Default for SimpleCounter {
+    fn default() -> SimpleCounter {
+        SimpleCounter::new()
+    }
+}
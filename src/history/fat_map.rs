@@ -15,17 +15,15 @@
  *  You should have received a copy of the GNU General Public License
  *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
-use core::hash::BuildHasher;
-use core::hash::Hasher;
 use std::collections::HashMap;
-use std::collections::hash_map::DefaultHasher;
 
 use history::{
-    HistorySource,
+    HistorySource, UNFINISHED_BYTE_MESSAGE,
     ContextState,
     CollectedContextStates,
     updated_bit_history, compare_for_equal_prefix,
 };
+use util::hash::{ContextHasher, DefaultContextHasher};
 
 #[derive(Clone)]
 struct LocalContextState {
@@ -33,40 +31,60 @@ struct LocalContextState {
     bit_history: u32,
 }
 
-pub struct FatMapHistorySource {
+pub struct FatMapHistorySource<H: ContextHasher = DefaultContextHasher> {
     input: Vec<u8>,
     input_cursor: usize,
     bit_index: usize,
     max_order: usize,
     maps: Vec<HashMap<u64, Vec<LocalContextState>>>,
+    hasher: H,
 }
 
 impl FatMapHistorySource {
-    fn compute_hash(&self, order: usize) -> u64 {
-        let map = &self.maps[(order * 8) + self.bit_index];
-        let mut hasher: DefaultHasher = map.hasher().build_hasher();
-        hasher.write(
-            &self.input[self.input_cursor - order..self.input_cursor]);
-        hasher.write_u32((256 + self.input[self.input_cursor] as u32) >>
-            (self.bit_index + 1));
-        hasher.finish()
+    pub fn new(max_window_size: usize, max_order: usize)
+               -> FatMapHistorySource {
+        FatMapHistorySource::new_with_hasher(
+            max_window_size, max_order, DefaultContextHasher)
     }
 }
 
-impl HistorySource for FatMapHistorySource {
-    fn new(max_window_size: usize, max_order: usize) -> FatMapHistorySource {
+impl<H: ContextHasher> FatMapHistorySource<H> {
+    pub fn new_with_hasher(max_window_size: usize, max_order: usize,
+                           hasher: H) -> FatMapHistorySource<H> {
         FatMapHistorySource {
             input: Vec::with_capacity(max_window_size),
             input_cursor: 0,
             bit_index: 7,
             max_order,
             maps: vec![HashMap::new(); (max_order + 1) * 8],
+            hasher,
         }
     }
 
+    /** Number of contexts beyond the first one stored under each hash */
+    pub fn hash_collisions_count(&self) -> usize {
+        self.maps.iter().flat_map(|map| map.values())
+            .map(|contexts| contexts.len() - 1).sum()
+    }
+
+    fn compute_hash(&self, order: usize) -> u64 {
+        self.hasher.hash(
+            &self.input[self.input_cursor - order..self.input_cursor],
+            (256 + self.input[self.input_cursor] as u32) >>
+                (self.bit_index + 1))
+    }
+}
+
+impl<H: ContextHasher> HistorySource for FatMapHistorySource<H> {
+    fn new(max_window_size: usize, max_order: usize)
+           -> FatMapHistorySource<H> {
+        FatMapHistorySource::new_with_hasher(
+            max_window_size, max_order, H::default())
+    }
+
     fn start_new_byte(&mut self) {
-        assert_eq!(self.bit_index, 7);
-        assert_eq!(self.input_cursor, self.input.len());
+        assert!(self.bit_index == 7 && self.input_cursor == self.input.len(),
+                "{}", UNFINISHED_BYTE_MESSAGE);
         assert_ne!(self.input.len(), self.input.capacity(),
                    "input window is filled up, but sliding is not implemented");
         self.input.push(0);
@@ -79,7 +97,7 @@ impl HistorySource for FatMapHistorySource {
             let hash = self.compute_hash(order);
             let vec_opt: Option<&Vec<_>> = map.get(&hash);
             match vec_opt.into_iter().
-                flat_map(|vec| vec.into_iter().find(|item| {
+                flat_map(|vec| vec.iter().find(|item| {
                     compare_for_equal_prefix(
                         &self.input, self.input_cursor - order,
                         item.byte_index, self.bit_index, order)
@@ -98,7 +116,7 @@ impl HistorySource for FatMapHistorySource {
         for order in 0..(self.max_order.min(self.input_cursor) + 1) {
             let hash = self.compute_hash(order);
             let map = &mut self.maps[(order * 8) + self.bit_index];
-            let vec: &mut Vec<_> = map.entry(hash).or_insert(Vec::new());
+            let vec: &mut Vec<_> = map.entry(hash).or_default();
             let input = &self.input;
             let byte_index = self.input_cursor - order;
             let bit_index = self.bit_index;
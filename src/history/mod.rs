@@ -52,6 +52,9 @@ impl CollectedContextStates {
     }
 }
 
+const UNFINISHED_BYTE_MESSAGE: &str =
+    "start_new_byte called before all 8 bits of previous byte were processed";
+
 pub trait HistorySource {
     fn new(max_window_size: usize, max_order: usize) -> Self;
 
@@ -16,7 +16,7 @@
  *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 use history::{
-    HistorySource,
+    HistorySource, UNFINISHED_BYTE_MESSAGE,
     ContextState,
     CollectedContextStates,
     updated_bit_history, get_bit, compare_for_equal_prefix,
@@ -40,8 +40,8 @@ impl HistorySource for NaiveHistorySource {
     }
 
     fn start_new_byte(&mut self) {
-        assert_eq!(self.bit_index, 7);
-        assert_eq!(self.input_cursor, self.input.len());
+        assert!(self.bit_index == 7 && self.input_cursor == self.input.len(),
+                "{}", UNFINISHED_BYTE_MESSAGE);
         assert_ne!(self.input.len(), self.input.capacity(),
                    "input window is filled up, but sliding is not implemented");
         self.input.push(0);
@@ -64,7 +64,7 @@ impl HistorySource for NaiveHistorySource {
                     bit_history = updated_bit_history(bit_history, next_bit);
                 }
             }
-            assert_eq!(last_occurrence_index_opt == None, bit_history == 1);
+            assert_eq!(last_occurrence_index_opt.is_none(), bit_history == 1);
             if let Some(last_occurrence_index) = last_occurrence_index_opt {
                 bit_histories.items.push(
                     ContextState { last_occurrence_index, bit_history });
@@ -22,8 +22,9 @@ use std::ops;
 use std::collections::HashMap;
 
 use ::PRINT_DEBUG;
+use estimators::SimpleCounter;
 use history::{
-    HistorySource,
+    HistorySource, UNFINISHED_BYTE_MESSAGE,
     ContextState,
     CollectedContextStates,
     make_bit_run_history, updated_bit_history, get_bit, bytes_differ_on,
@@ -34,25 +35,96 @@ use history::{
 const OVER_PROVISIONING_FACTOR: usize = 10;
 const OVER_PROVISIONING_CONSTANT: usize = 100;
 
+pub const MAX_EDGE_VISITS_COUNT: usize = 63;
+
+fn capped_edge_visits_count(edge_visits_count: usize) -> usize {
+    MAX_EDGE_VISITS_COUNT.min(edge_visits_count)
+}
+
 pub struct TreeHistorySource {
     pub tree: Tree,
     pub active_contexts: ActiveContexts,
     bit_index: usize,
 }
 
-impl HistorySource for TreeHistorySource {
-    fn new(max_window_size: usize, max_order: usize) -> TreeHistorySource {
+impl TreeHistorySource {
+    pub fn new_with_allocation(max_window_size: usize, max_order: usize,
+                               allocation: NodesAllocation)
+                               -> TreeHistorySource {
+        TreeHistorySource::new_with_config(
+            max_window_size, max_order, allocation, 1, false)
+    }
+
+    /**
+     * Degenerate switch threshold is the number of bytes differing from the
+     * repeated one needed to switch from degenerate to proper state, only 1
+     * gives the same states as naive and fat map sources. Node counters
+     * are kept outside of nodes, so disabling them costs no memory.
+     */
+    pub fn new_with_config(max_window_size: usize, max_order: usize,
+                           allocation: NodesAllocation,
+                           degenerate_switch_threshold: usize,
+                           node_counters: bool) -> TreeHistorySource {
         assert!(max_window_size > 0);
-        let nodes = Nodes::new(Nodes::NUM_ROOTS.max(max_window_size - 1));
+        let mut nodes = Nodes::new_with_allocation(
+            Nodes::NUM_ROOTS.max(max_window_size - 1), allocation);
+        if node_counters {
+            nodes.enable_counters();
+        }
         TreeHistorySource {
-            tree: Tree::new(nodes, max_window_size, 0),
+            tree: Tree::new(nodes, max_window_size, 0,
+                            degenerate_switch_threshold),
             active_contexts: ActiveContexts::new(max_order),
             bit_index: 7,
         }
     }
 
+    /**
+     * Removes leftmost suffixes until window has at most given size, with
+     * bump only allocation window can only be shrunk to empty one
+     */
+    pub fn shrink_to(&mut self, window_size: usize) {
+        assert!(self.bit_index == 7 &&
+                    self.tree.window_cursor == self.tree.window.len(),
+                "{}", UNFINISHED_BYTE_MESSAGE);
+        if self.tree.nodes.allocation == NodesAllocation::BumpOnly &&
+            window_size < self.tree.window_size {
+            assert_eq!(window_size, 0,
+                       "bump only allocation can only shrink to empty window");
+            self.active_contexts.keep_only(0);
+            self.tree.reset();
+            return;
+        }
+        while self.tree.window_size > window_size {
+            self.tree.remove_leftmost_suffix(&mut self.active_contexts);
+        }
+        if self.tree.window_size == 0 {
+            assert!(self.tree.tree_state == TreeState::Degenerate);
+            assert!(self.active_contexts.items().is_empty());
+            self.tree.reset();
+        }
+    }
+
+    /**
+     * Empties the window, releases memory held by nodes and window and
+     * resets statistics, source behaves like a fresh one afterwards
+     */
+    pub fn clear(&mut self) {
+        self.shrink_to(0);
+        self.tree.stats = TreeStats::default();
+    }
+}
+
+impl HistorySource for TreeHistorySource {
+    fn new(max_window_size: usize, max_order: usize) -> TreeHistorySource {
+        TreeHistorySource::new_with_allocation(
+            max_window_size, max_order, NodesAllocation::FreeList)
+    }
+
     fn start_new_byte(&mut self) {
-        assert_eq!(self.bit_index, 7);
+        assert!(self.bit_index == 7 &&
+                    self.tree.window_cursor == self.tree.window.len(),
+                "{}", UNFINISHED_BYTE_MESSAGE);
         self.active_contexts.shift(&mut self.tree);
         self.tree.start_new_byte(&mut self.active_contexts);
     }
@@ -150,21 +222,45 @@ pub struct Context {
 }
 
 impl Context {
+    /** Sentinel for unknown count, it must never be used for computations */
+    const UNKNOWN_EDGE_VISITS_COUNT: i32 = -1;
+
+    pub fn suffix_index(&self) -> usize {
+        self.suffix_index.index
+    }
+
+    pub fn incoming_edge_visits_count(&self) -> usize {
+        assert_ne!(self.incoming_edge_visits_count,
+                   Context::UNKNOWN_EDGE_VISITS_COUNT);
+        debug_assert!(self.incoming_edge_visits_count >= 0 &&
+            self.incoming_edge_visits_count <= MAX_EDGE_VISITS_COUNT as i32,
+                      "context = {}", self);
+        self.incoming_edge_visits_count as usize
+    }
+
+    fn set_incoming_edge_visits_count(&mut self, edge_visits_count: usize) {
+        self.incoming_edge_visits_count =
+            capped_edge_visits_count(edge_visits_count) as i32;
+    }
+
     fn descend(&mut self, tree: &mut Tree, order: usize, bit_index: usize) {
         assert!(!self.in_leaf);
+        tree.stats.descends += 1;
         let direction: Direction =
             get_bit(tree.window[tree.window_cursor], bit_index).into();
         self.direction_from_parent = Some(direction);
         let node_index = self.node_index;
-        self.incoming_edge_visits_count = {
+        let edge_visits_count = {
             let node = &tree.nodes()[node_index];
             if direction == Direction::Left {
                 node.left_count()
             } else {
                 node.right_count()
             }
-        } as i32;
+        };
+        self.set_incoming_edge_visits_count(edge_visits_count);
         tree.nodes_mut()[node_index].increment_edge_counters(direction);
+        tree.nodes_mut().update_counter(node_index, direction);
         let child = tree.nodes()[node_index].child(direction);
         if child.is_window_index() {
             self.in_leaf = true;
@@ -188,7 +284,7 @@ impl Context {
             suffix_index: WindowIndex {
                 index: self.suffix_index.index - offset
             },
-            node_index: NodeIndex::new(<i32>::max_value()),
+            node_index: NodeIndex::new(i32::MAX),
             incoming_edge_visits_count: 0,
             ..self.clone()
         }
@@ -230,15 +326,16 @@ impl ActiveContexts {
         let root_index = tree.get_root_node_index();
         tree.nodes[root_index].text_start = tree.window_cursor as u32;
         let root = &tree.nodes[root_index];
-        let incoming_edge_visits_count =
-            63.min(root.left_count() + root.right_count()) as i32;
-        self.items.insert(0, Context {
+        let mut context = Context {
             suffix_index: WindowIndex::new((tree.window_cursor - 1) as i32),
             node_index: root_index,
             in_leaf: false,
-            incoming_edge_visits_count,
+            incoming_edge_visits_count: Context::UNKNOWN_EDGE_VISITS_COUNT,
             direction_from_parent: None,
-        });
+        };
+        context.set_incoming_edge_visits_count(
+            root.left_count() + root.right_count());
+        self.items.insert(0, context);
     }
 
     fn max_order(&self) -> usize {
@@ -250,7 +347,7 @@ impl ActiveContexts {
     }
 
     fn keep_only(&mut self, count: usize) {
-        self.items.split_off(count);
+        self.items.truncate(count);
     }
 
     pub fn items(&self) -> &[Context] {
@@ -321,6 +418,31 @@ impl fmt::Display for ActiveContexts {
     }
 }
 
+/** Branch taken by Tree::remove_leftmost_suffix */
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DeletionOutcome {
+    InDegenerateState,
+    PrefixRepeated,
+    RootToDegenerate,
+    RootChildPromoted,
+    NodeInActiveContexts,
+    NodeNotInActiveContexts,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TreeStats {
+    pub descends: u64,
+    pub edge_splits: u64,
+    pub degenerate_root_edge_splits: u64,
+    pub delayed_degenerate_switches: u64,
+    pub removals_in_degenerate_state: u64,
+    pub removals_skipped_for_repeated_prefix: u64,
+    pub removals_promoting_root_child: u64,
+    pub removals_making_tree_degenerate: u64,
+    pub removals_of_node_in_active_contexts: u64,
+    pub removals_of_node_not_in_active_contexts: u64,
+}
+
 pub struct Tree {
     nodes: Nodes,
     window: Vec<u8>,
@@ -330,11 +452,18 @@ pub struct Tree {
     max_window_size: usize,
     pub tree_state: TreeState,
     root_index: i32,
+    degenerate_switch_threshold: usize,
+    /** Indices of bytes differing from the repeated one in degenerate state */
+    anomalies: Vec<usize>,
+    rebuild_pending: bool,
+    stats: TreeStats,
 }
 
 impl Tree {
     fn start_new_byte(&mut self, active_contexts: &mut ActiveContexts) {
         if self.window_size == self.max_window_size {
+            assert_eq!(self.nodes.allocation, NodesAllocation::FreeList,
+                       "window can't slide with bump only allocation");
             assert_eq!(self.window_start + self.max_window_size,
                        self.window_cursor);
             assert_eq!(self.window_cursor, self.window.len());
@@ -347,19 +476,27 @@ impl Tree {
     }
 
     pub fn remove_leftmost_suffix(&mut self,
-                                  active_contexts: &mut ActiveContexts) {
+                                  active_contexts: &mut ActiveContexts)
+                                  -> DeletionOutcome {
         if self.tree_state == TreeState::Degenerate {
+            self.stats.removals_in_degenerate_state += 1;
+            if self.anomalies.first() == Some(&self.window_start) {
+                self.anomalies.remove(0);
+            }
             self.window[self.window_start] = 0;
             self.window_start += 1;
             self.window_size -= 1;
-            return;
+            // repeated byte is gone, let next extension rebuild the tree
+            self.rebuild_pending |= !self.anomalies.is_empty() &&
+                self.repeated_byte_index().is_none();
+            return DeletionOutcome::InDegenerateState;
         }
         let mut parent_node_index_opt = None;
         let mut node_direction_opt = None;
         let mut node_index = self.get_root_node_index();
         let mut leaf_direction_opt = None;
         let mut leaf_sibling_opt = None;
-        while leaf_direction_opt == None {
+        while leaf_direction_opt.is_none() {
             let depth = self.nodes[node_index].depth();
             let direction: Direction = get_bit(
                 self.window[self.window_start + depth / 8], 7 - (depth % 8),
@@ -382,12 +519,13 @@ impl Tree {
         let node_found_in_active_contexts = active_contexts.items.iter()
             .find(|ctx| ctx.node_index.index == node_index.index).is_some();
         if PRINT_DEBUG { print!("DELETING: "); }
-        if leaf_window_index.index > self.window_start {
+        let outcome = if leaf_window_index.index > self.window_start {
             if PRINT_DEBUG {
                 println!("skipped because prefix was repeated");
                 println!("window start = {}, active contexts = {}",
                          self.window_start, active_contexts);
             }
+            self.stats.removals_skipped_for_repeated_prefix += 1;
             let mut new_active_contexts_count = active_contexts.count();
             for (order, ctx) in active_contexts.items.iter().enumerate().rev() {
                 assert!(ctx.suffix_index.index >= self.window_start);
@@ -397,6 +535,7 @@ impl Tree {
                 }
             }
             active_contexts.keep_only(new_active_contexts_count);
+            DeletionOutcome::PrefixRepeated
         } else if node_index.is_root() {
             let root_index = node_index;
             assert_eq!(parent_node_index_opt, None);
@@ -411,6 +550,7 @@ impl Tree {
                 if PRINT_DEBUG {
                     println!("root node child = {:?}", leaf_direction);
                 }
+                self.stats.removals_promoting_root_child += 1;
                 assert!(leaf_sibling.is_node_index());
                 let leaf_sibling_node_index = leaf_sibling.to_node_index();
                 let mut leaf_sibling_node =
@@ -418,22 +558,26 @@ impl Tree {
                 leaf_sibling_node.text_start =
                     self.nodes[root_index].text_start;
                 self.nodes.update_node(root_index, leaf_sibling_node);
+                self.nodes.copy_counter(leaf_sibling_node_index, root_index);
                 self.nodes.delete_node(leaf_sibling_node_index);
                 if PRINT_DEBUG { self.print(); }
+                DeletionOutcome::RootChildPromoted
             } else {
                 if PRINT_DEBUG {
                     println!("root node and changing tree state to degenerate");
                 }
+                self.stats.removals_making_tree_degenerate += 1;
                 self.tree_state = TreeState::Degenerate;
                 active_contexts.keep_only(0);
                 self.nodes.update_node(root_index, Node::INVALID);
+                DeletionOutcome::RootToDegenerate
             }
         } else if node_found_in_active_contexts {
             assert!(!node_index.is_root());
             let leaf_found_in_active_contexts = active_contexts.items.iter()
                 .find(|ctx| ctx.node_index.index == node_index.index &&
                     ctx.direction_from_parent == Some(leaf_direction) &&
-                    ctx.in_leaf == true).is_some();
+                    ctx.in_leaf).is_some();
             assert!(!leaf_found_in_active_contexts,
                     "triggered situation assumed to be impossible");
             if PRINT_DEBUG {
@@ -442,6 +586,7 @@ impl Tree {
                          leaf_direction, node_index);
                 println!("active contexts = {}", active_contexts);
             }
+            self.stats.removals_of_node_in_active_contexts += 1;
             let parent_node_index = parent_node_index_opt.unwrap();
             let node_direction = node_direction_opt.unwrap();
             if leaf_sibling.is_window_index() {
@@ -479,12 +624,14 @@ impl Tree {
             }
             self.nodes.delete_node(node_index);
             if PRINT_DEBUG { self.print(); }
+            DeletionOutcome::NodeInActiveContexts
         } else {
             if PRINT_DEBUG {
                 println!("child = {:?} of node = {:?} not in active contexts",
                          leaf_direction, node_index);
                 println!("active contexts = {}", active_contexts);
             }
+            self.stats.removals_of_node_not_in_active_contexts += 1;
             assert!(!node_index.is_root());
             assert!(!node_found_in_active_contexts);
             let parent_node_index = parent_node_index_opt.unwrap();
@@ -493,10 +640,12 @@ impl Tree {
                 leaf_sibling;
             self.nodes.delete_node(node_index);
             if PRINT_DEBUG { self.print(); }
-        }
+            DeletionOutcome::NodeNotInActiveContexts
+        };
         self.window[self.window_start] = 0;
         self.window_start += 1;
         self.window_size -= 1;
+        outcome
     }
 
     pub fn check_integrity(&self, max_order: usize) {
@@ -586,6 +735,41 @@ impl Tree {
         }
     }
 
+    /** Graphviz representation, leaves are labelled with window indices */
+    pub fn to_dot(&self) -> String {
+        let mut result = String::from("digraph tree {\n");
+        if self.tree_state == TreeState::Proper {
+            self.write_dot_node(NodeIndex::new(self.root_index), &mut result);
+        }
+        result.push_str("}\n");
+        result
+    }
+
+    fn write_dot_node(&self, node_index: NodeIndex, result: &mut String) {
+        use std::fmt::Write;
+        let node = self.nodes[node_index];
+        assert!(node.is_valid());
+        writeln!(result, "  node{} [label=\"depth = {}\\nleft = {}, \
+                          right = {}\"];", node_index.index, node.depth(),
+                 node.left_count(), node.right_count()).unwrap();
+        for &(direction, bit) in
+            [(Direction::Left, 0), (Direction::Right, 1)].iter() {
+            let child = node.child(direction);
+            if child.is_node_index() {
+                let child_index = child.to_node_index();
+                writeln!(result, "  node{} -> node{} [label={}];",
+                         node_index.index, child_index.index, bit).unwrap();
+                self.write_dot_node(child_index, result);
+            } else {
+                let window_index = child.to_window_index().index;
+                writeln!(result, "  leaf{} [shape=box, label={}];",
+                         window_index, window_index).unwrap();
+                writeln!(result, "  node{} -> leaf{} [label={}];",
+                         node_index.index, window_index, bit).unwrap();
+            }
+        }
+    }
+
     fn print_node(&self, node_index: NodeIndex, depth: usize) {
         let node = self.nodes[node_index];
         assert!(node.is_valid());
@@ -606,8 +790,10 @@ impl Tree {
         }
     }
 
-    pub fn new(nodes: Nodes, max_window_size: usize, root_index: i32) -> Tree {
+    pub fn new(nodes: Nodes, max_window_size: usize, root_index: i32,
+               degenerate_switch_threshold: usize) -> Tree {
         assert!(max_window_size > 0);
+        assert!(degenerate_switch_threshold > 0);
         Tree {
             nodes,
             window: Vec::with_capacity(OVER_PROVISIONING_CONSTANT +
@@ -618,7 +804,73 @@ impl Tree {
             max_window_size,
             tree_state: TreeState::Degenerate,
             root_index,
+            degenerate_switch_threshold,
+            anomalies: Vec::new(),
+            rebuild_pending: false,
+            stats: TreeStats::default(),
+        }
+    }
+
+    /** Drops all nodes, rewinds window to the beginning, shrinks buffers */
+    fn reset(&mut self) {
+        let root_node_index = self.get_root_node_index();
+        self.nodes.update_node(root_node_index, Node::INVALID);
+        self.nodes.reset();
+        self.window.clear();
+        self.window.shrink_to(OVER_PROVISIONING_CONSTANT +
+            self.max_window_size * OVER_PROVISIONING_FACTOR);
+        self.window_start = 0;
+        self.window_cursor = 0;
+        self.window_size = 0;
+        self.tree_state = TreeState::Degenerate;
+        self.anomalies.clear();
+        self.rebuild_pending = false;
+    }
+
+    /** First byte of the window that isn't an anomaly, if any */
+    fn repeated_byte_index(&self) -> Option<usize> {
+        assert!(self.tree_state == TreeState::Degenerate);
+        (self.window_start..self.window_cursor)
+            .find(|index| !self.anomalies.contains(index))
+    }
+
+    /**
+     * Replays window contents (including already processed bits of current
+     * byte) switching to proper state on first anomaly
+     */
+    fn rebuild(&mut self, active_contexts: &mut ActiveContexts,
+               replayed_bits_count: usize) {
+        assert!(self.tree_state == TreeState::Degenerate);
+        self.stats.delayed_degenerate_switches += 1;
+        let max_order = active_contexts.max_order();
+        let complete_bytes =
+            self.window[self.window_start..self.window_cursor].to_vec();
+        let current_byte = self.window[self.window_cursor];
+        let degenerate_switch_threshold = self.degenerate_switch_threshold;
+        self.degenerate_switch_threshold = 1;
+        self.anomalies.clear();
+        self.rebuild_pending = false;
+        self.nodes.reset();
+        self.window.truncate(self.window_start);
+        self.window_cursor = self.window_start;
+        self.window_size = 0;
+        active_contexts.keep_only(0);
+        for &byte in complete_bytes.iter() {
+            active_contexts.shift(self);
+            self.start_new_byte(active_contexts);
+            for bit_index in (0..7 + 1).rev() {
+                self.extend(active_contexts, get_bit(byte, bit_index),
+                            bit_index, max_order);
+            }
+            self.window_cursor += 1;
+        }
+        active_contexts.shift(self);
+        self.start_new_byte(active_contexts);
+        for bit_index in (8 - replayed_bits_count..7 + 1).rev() {
+            self.extend(active_contexts, get_bit(current_byte, bit_index),
+                        bit_index, max_order);
         }
+        self.degenerate_switch_threshold = degenerate_switch_threshold;
     }
 
     pub fn get_root_node_index(&self) -> NodeIndex {
@@ -629,6 +881,24 @@ impl Tree {
         &self.nodes
     }
 
+    pub fn stats(&self) -> &TreeStats {
+        &self.stats
+    }
+
+    /** Number of internal nodes in a tree built online from whole input,
+     * computed offline by sorting suffixes truncated to max_order + 1 bytes.
+     * Each pair of adjacent distinct suffixes that aren't prefixes of each
+     * other meets at exactly one branching node. */
+    pub fn count_distinct_suffixes(input: &[u8], max_order: usize) -> usize {
+        let mut suffixes: Vec<&[u8]> = (0..input.len()).map(|start| {
+            &input[start..input.len().min(start + max_order + 1)]
+        }).collect();
+        suffixes.sort();
+        suffixes.dedup();
+        suffixes.windows(2).filter(|pair| !pair[1].starts_with(pair[0]))
+            .count()
+    }
+
     pub fn gather_states(&self, active_contexts: &ActiveContexts,
                          collected_states: &mut CollectedContextStates,
                          bit_index: usize) {
@@ -645,12 +915,11 @@ impl Tree {
                         if node.depth() == order * 8 + 7 - bit_index {
                             node.history_state()
                         } else {
-                            assert_ne!(context.incoming_edge_visits_count, -1);
                             let repeated_bit = get_bit(
                                 self.window[order + last_occurrence_index],
                                 bit_index);
                             make_bit_run_history(
-                                context.incoming_edge_visits_count as usize,
+                                context.incoming_edge_visits_count(),
                                 repeated_bit)
                         };
                     if bit_history != 1 {
@@ -665,22 +934,34 @@ impl Tree {
             }
             TreeState::Degenerate => {
                 assert_eq!(active_contexts.count(), 0);
+                let repeated_byte_index = match self.repeated_byte_index() {
+                    Some(index) if self.anomalies.last() !=
+                        Some(&self.window_cursor) => index,
+                    _ => return,
+                };
                 let count = (active_contexts.max_order() + 1)
                     .min(self.window_size - 1);
-                for order in 0..count {
-                    collected_states.items.push(ContextState {
-                        last_occurrence_index: self.window_cursor - order - 1,
+                // whole window is treated as a run of a single byte value
+                let repeated_bit =
+                    get_bit(self.window[repeated_byte_index], bit_index);
+                let window_cursor = self.window_cursor;
+                let window_size = self.window_size;
+                collected_states.items.extend((0..count).map(|order| {
+                    ContextState {
+                        last_occurrence_index: window_cursor - order - 1,
                         bit_history: make_bit_run_history(
-                            self.window_size - order - 1,
-                            get_bit(self.window[self.window_start], bit_index)),
-                    });
-                }
+                            window_size - order - 1, repeated_bit),
+                    }
+                }));
             }
         }
     }
 
     pub fn extend(&mut self, active_contexts: &mut ActiveContexts,
                   incoming_bit: bool, bit_index: usize, max_order: usize) {
+        if self.rebuild_pending {
+            self.rebuild(active_contexts, 7 - bit_index);
+        }
         self.window[self.window_cursor] |= (incoming_bit as u8) << bit_index;
         match self.tree_state {
             TreeState::Proper => {
@@ -713,7 +994,7 @@ impl Tree {
                             context.in_leaf || self.nodes()[node_index].depth()
                                 >= order * 8,
                             "order = {}, context = {}", order, context);
-                        self.split_edge(&context, order, bit_index);
+                        self.split_edge(context, order, bit_index);
                         if PRINT_DEBUG { self.print(); }
                         assert_eq!(count - 1, order);
                         count = order;
@@ -723,15 +1004,31 @@ impl Tree {
             }
             TreeState::Degenerate => {
                 assert_eq!(active_contexts.count(), 0);
-                if self.window_size >= 2 && bytes_differ_on(
-                    self.window_cursor - 1, self.window_cursor, bit_index,
-                    &self.window,
-                ) {
+                if self.anomalies.last() == Some(&self.window_cursor) {
+                    return;
+                }
+                let repeated_byte_index = match self.repeated_byte_index() {
+                    Some(index) => index,
+                    None => return,
+                };
+                if !bytes_differ_on(repeated_byte_index, self.window_cursor,
+                                    bit_index, &self.window) {
+                    return;
+                }
+                self.anomalies.push(self.window_cursor);
+                if self.anomalies.len() < self.degenerate_switch_threshold {
+                    return;
+                }
+                if self.anomalies.len() == 1 {
+                    // whole window is a run, so the tree is a simple chain
+                    self.anomalies.clear();
                     let order = max_order.min(self.window_size - 2);
                     self.split_degenerate_root_edge(order, bit_index);
                     self.tree_state = TreeState::Proper;
-                    if PRINT_DEBUG { self.print(); }
+                } else {
+                    self.rebuild(active_contexts, 8 - bit_index);
                 }
+                if PRINT_DEBUG { self.print(); }
             }
         }
     }
@@ -742,6 +1039,7 @@ impl Tree {
 
     fn split_edge(&mut self, context: &Context, context_order: usize,
                   bit_index: usize) {
+        self.stats.edge_splits += 1;
         let direction: Direction =
             get_bit(self.window[self.window_cursor], bit_index).into();
         let node_index = context.node_index;
@@ -761,6 +1059,10 @@ impl Tree {
                 print!(", new parent = {}, new child = {}", node, new_node);
             }
             self.nodes.update_node(node_index, node);
+            self.nodes.copy_counter(
+                node_index, node.children[!direction].to_node_index());
+            self.set_split_counter(
+                node_index, context.incoming_edge_visits_count(), direction);
         } else {
             if PRINT_DEBUG {
                 print!("SPLIT: leaf edge, order = {}", context_order);
@@ -775,21 +1077,23 @@ impl Tree {
                 self.window_cursor - context_order);
             new_node.children[!direction] =
                 NodeChild::from_window_index(context.suffix_index.index);
+            let new_node_child = self.nodes.add_node(new_node);
             node.children[context.direction_from_parent.unwrap()] =
-                self.nodes.add_node(new_node);
+                new_node_child;
             if PRINT_DEBUG {
                 print!(", new parent = {}, new child = {}", node, new_node);
             }
             self.nodes.update_node(node_index, node);
+            self.set_split_counter(new_node_child.to_node_index(),
+                                   context.incoming_edge_visits_count(),
+                                   direction);
         }
         if PRINT_DEBUG { println!(", context = {}", context); }
     }
 
     fn setup_split_edge(&self, context: &Context, context_order: usize,
                         bit_index: usize, text_start: usize) -> Node {
-        assert_ne!(context.incoming_edge_visits_count, -1);
-        let incoming_edge_visits_count =
-            context.incoming_edge_visits_count as usize;
+        let incoming_edge_visits_count = context.incoming_edge_visits_count();
         let bit = get_bit(self.window[self.window_cursor], bit_index);
         let direction: Direction = bit.into();
         let bit_history = updated_bit_history(make_bit_run_history(
@@ -808,6 +1112,7 @@ impl Tree {
             println!("SPLIT: Splitting degenerate root edge, order = {}",
                      context_order);
         }
+        self.stats.degenerate_root_edge_splits += 1;
         let bit = get_bit(self.window[self.window_cursor], bit_index);
         let direction: Direction = bit.into();
         let mut last_node_index_opt = None;
@@ -820,26 +1125,43 @@ impl Tree {
                 direction.fold(|| branching_child, || chained_child),
                 direction.fold(|| chained_child, || branching_child),
             ];
-            let bit_history = updated_bit_history(make_bit_run_history(
-                self.window_cursor - current_context_order, !bit), bit);
+            let run_length = distance_to_end - self.window_start;
+            let bit_history = updated_bit_history(
+                make_bit_run_history(run_length, !bit), bit);
+            let run_edge_visits_count = capped_edge_visits_count(run_length);
             let node = Node::new(
                 distance_to_end,
                 current_context_order * 8 + 7 - bit_index,
-                direction.fold(|| 1, || 63.min(distance_to_end)),
-                direction.fold(|| 63.min(distance_to_end), || 1),
+                direction.fold(|| 1, || run_edge_visits_count),
+                direction.fold(|| run_edge_visits_count, || 1),
                 bit_history,
                 children,
             );
             if current_context_order == 0 {
                 let root_node_index = self.get_root_node_index();
                 self.nodes.update_node(root_node_index, node);
+                self.set_split_counter(root_node_index, run_length, direction);
                 last_node_index_opt = None;
             } else {
-                last_node_index_opt = Some(self.nodes.add_node(node));
+                let node_child = self.nodes.add_node(node);
+                self.set_split_counter(
+                    node_child.to_node_index(), run_length, direction);
+                last_node_index_opt = Some(node_child);
             }
         }
         assert_eq!(last_node_index_opt, None);
     }
+
+    /** Counter of a new node after a run of visits ended by a split */
+    fn set_split_counter(&mut self, node_index: NodeIndex, run_length: usize,
+                         direction: Direction) {
+        if self.nodes.counters_opt.is_some() {
+            let bit = direction == Direction::Right;
+            let mut counter = SimpleCounter::after_run(run_length, !bit);
+            counter.update(bit);
+            self.nodes.set_counter(node_index, counter);
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -857,7 +1179,7 @@ impl NodeChild {
     }
 
     fn from_node_index(node_index: usize) -> NodeChild {
-        assert!(node_index >= Nodes::NUM_ROOTS && node_index <= 0x7fff_ffff);
+        assert!((Nodes::NUM_ROOTS..=0x7fff_ffff).contains(&node_index));
         NodeChild { index: !(node_index as i32) }
     }
 
@@ -873,7 +1195,7 @@ impl NodeChild {
         self.index < 0
     }
 
-    fn to_window_index(&self) -> WindowIndex {
+    fn to_window_index(self) -> WindowIndex {
         WindowIndex::new(self.index)
     }
 
@@ -913,7 +1235,6 @@ impl WindowIndex {
 #[derive(Clone, Copy)]
 pub struct Node {
     children: [NodeChild; 2],
-    // counter: SimpleCounter,
     text_start: u32,
     history_state: u16,
     depth: u16,
@@ -938,6 +1259,8 @@ impl Node {
         assert!((depth as u64) < 1u64 << 16);
         assert!((left_count as u64) < 1u64 << 16);
         assert!((right_count as u64) < 1u64 << 16);
+        debug_assert!(left_count <= MAX_EDGE_VISITS_COUNT);
+        debug_assert!(right_count <= MAX_EDGE_VISITS_COUNT);
         assert!((history_state as u64) < 1u64 << 16);
         Node {
             children,
@@ -962,11 +1285,11 @@ impl Node {
         self.depth as usize
     }
 
-    fn left_count(&self) -> usize {
+    pub fn left_count(&self) -> usize {
         self.left_count as usize
     }
 
-    fn right_count(&self) -> usize {
+    pub fn right_count(&self) -> usize {
         self.right_count as usize
     }
 
@@ -981,9 +1304,11 @@ impl Node {
     fn increment_edge_counters(&mut self, direction: Direction) {
         match direction {
             Direction::Left =>
-                self.left_count = 63.min(self.left_count + 1),
+                self.left_count = capped_edge_visits_count(
+                    self.left_count() + 1) as u16,
             Direction::Right =>
-                self.right_count = 63.min(self.right_count + 1),
+                self.right_count = capped_edge_visits_count(
+                    self.right_count() + 1) as u16,
         }
         self.history_state = updated_bit_history(
             self.history_state(), direction.fold(|| false, || true)) as u16;
@@ -998,27 +1323,100 @@ impl fmt::Display for Node {
     }
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NodesAllocation {
+    /** Deleted nodes are chained in a list and reused */
+    FreeList,
+    /**
+     * Nodes are never deleted, window must hold whole input, clearing drops
+     * all nodes at once instead of removing suffixes one by one
+     */
+    BumpOnly,
+}
+
 pub struct Nodes {
     items: Vec<Node>,
+    nodes_limit: usize,
+    initial_capacity: usize,
+    allocation: NodesAllocation,
     last_deleted_node_idx_opt: Option<NodeIndex>,
     removed_nodes_count: usize,
+    /** Stationary counters indexed like nodes, absent unless enabled */
+    counters_opt: Option<Vec<SimpleCounter>>,
 }
 
 impl Nodes {
     const NUM_ROOTS: usize = 1;
+    pub const DEFAULT_INITIAL_CAPACITY: usize = 1 << 12;
 
     pub fn new(nodes_limit: usize) -> Nodes {
+        Nodes::new_with_allocation(nodes_limit, NodesAllocation::FreeList)
+    }
+
+    pub fn new_with_allocation(nodes_limit: usize,
+                               allocation: NodesAllocation) -> Nodes {
+        Nodes::new_with_capacity(
+            nodes_limit, Nodes::DEFAULT_INITIAL_CAPACITY, allocation)
+    }
+
+    /** Arena grows on demand from initial capacity up to nodes limit */
+    pub fn new_with_capacity(nodes_limit: usize, initial_capacity: usize,
+                             allocation: NodesAllocation) -> Nodes {
         assert!(nodes_limit >= Nodes::NUM_ROOTS);
-        let mut items = Vec::with_capacity(nodes_limit);
+        let capacity =
+            Nodes::NUM_ROOTS.max(initial_capacity).min(nodes_limit);
+        let mut items = Vec::with_capacity(capacity);
         (0..Nodes::NUM_ROOTS).for_each(|_| items.push(Node::INVALID));
         Nodes {
             items,
+            nodes_limit,
+            initial_capacity: capacity,
+            allocation,
             last_deleted_node_idx_opt: None,
             removed_nodes_count: 0,
+            counters_opt: None,
+        }
+    }
+
+    fn enable_counters(&mut self) {
+        assert_eq!(self.live_nodes_count(), 0);
+        self.counters_opt = Some(Vec::new());
+    }
+
+    /** Stationary counter of a node, if counters are enabled */
+    pub fn counter(&self, node_index: NodeIndex) -> Option<SimpleCounter> {
+        self.counters_opt.as_ref().map(|counters| {
+            assert!(self[node_index].is_valid());
+            counters[node_index.index]
+        })
+    }
+
+    fn set_counter(&mut self, node_index: NodeIndex, counter: SimpleCounter) {
+        if let Some(ref mut counters) = self.counters_opt {
+            if counters.len() <= node_index.index {
+                counters.resize(node_index.index + 1, SimpleCounter::HALF);
+            }
+            counters[node_index.index] = counter;
+        }
+    }
+
+    fn copy_counter(&mut self, source: NodeIndex, target: NodeIndex) {
+        if let Some(counter) = self.counter(source) {
+            self.set_counter(target, counter);
+        }
+    }
+
+    fn update_counter(&mut self, node_index: NodeIndex,
+                      direction: Direction) {
+        if let Some(ref mut counters) = self.counters_opt {
+            counters[node_index.index].update(direction == Direction::Right);
         }
     }
 
     fn add_node(&mut self, node: Node) -> NodeChild {
+        if self.allocation == NodesAllocation::BumpOnly {
+            return self.push_node(node);
+        }
         if let Some(last_deleted_node_index) = self.last_deleted_node_idx_opt {
             assert!(self.removed_nodes_count > 0);
             self.removed_nodes_count -= 1;
@@ -1036,18 +1434,29 @@ impl Nodes {
             NodeChild::from_node_index(last_deleted_node_index.index)
         } else {
             assert_eq!(self.removed_nodes_count, 0);
-            assert!(self.items.capacity() > self.items.len());
-            let node_child = NodeChild::from_node_index(self.items.len());
-            self.items.push(node);
-            node_child
+            self.push_node(node)
         }
     }
 
+    fn push_node(&mut self, node: Node) -> NodeChild {
+        assert!(self.items.len() < self.nodes_limit);
+        if self.items.len() == self.items.capacity() {
+            let grown_capacity =
+                self.nodes_limit.min(self.items.capacity() * 2);
+            self.items.reserve_exact(grown_capacity - self.items.len());
+        }
+        let node_child = NodeChild::from_node_index(self.items.len());
+        self.items.push(node);
+        node_child
+    }
+
     fn update_node(&mut self, node_index: NodeIndex, new_node: Node) {
         self.items[node_index.index] = new_node;
     }
 
     fn delete_node(&mut self, node_index: NodeIndex) {
+        assert_eq!(self.allocation, NodesAllocation::FreeList,
+                   "nodes can't be deleted with bump only allocation");
         assert!(self.items[node_index.index].is_valid());
         let mut node = Node::INVALID;
         node.children[Direction::Left] = NodeChild::INVALID;
@@ -1059,6 +1468,26 @@ impl Nodes {
         self.removed_nodes_count += 1;
     }
 
+    fn reset(&mut self) {
+        assert!(!self.items[0].is_valid());
+        self.items.truncate(Nodes::NUM_ROOTS);
+        self.items.shrink_to(self.initial_capacity);
+        if let Some(ref mut counters) = self.counters_opt {
+            counters.clear();
+            counters.shrink_to(self.initial_capacity);
+        }
+        self.last_deleted_node_idx_opt = None;
+        self.removed_nodes_count = 0;
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.items.capacity()
+    }
+
+    pub fn nodes_limit(&self) -> usize {
+        self.nodes_limit
+    }
+
     pub fn live_nodes_count(&self) -> usize {
         if self.items[0].is_valid() {
             self.items.len() - self.removed_nodes_count
@@ -17,8 +17,15 @@
  */
 extern crate core;
 
+pub mod coding;
+pub mod estimators;
 pub mod history;
+pub mod random;
+pub mod util;
 
 pub const MAX_ORDER: usize = 63;
 
+/** Keeps window indices and over-provisioned window buffers in bounds */
+pub const MAX_WINDOW_SIZE: usize = 1 << 26;
+
 pub const PRINT_DEBUG: bool = 2 + 2 == 5;
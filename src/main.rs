@@ -30,31 +30,122 @@ use demixer::history::naive::NaiveHistorySource;
 use demixer::history::fat_map::FatMapHistorySource;
 use demixer::history::tree::TreeHistorySource;
 
+const MAX_PROCESSED_BYTES: usize = 1234;
+
 fn main() {
     print_banner();
 
-    let args: Vec<String> = std::env::args().collect();
-    let history_source_type: &str = args.get(1).expect("provide type");
-    let file_name = args.get(2).expect("provide file name");
+    let options = parse_options(std::env::args().skip(1));
+    let history_source_type: &str = &options.history_source_type;
+    let file_name = &options.file_name;
 
-    let mut file = std::fs::File::open(file_name).expect("file not found");
-//    for byte in std::io::BufReader::new(file).bytes() {}
     let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer).unwrap();
-    std::mem::drop(file);
+    if file_name == "-" {
+        let stdin = std::io::stdin();
+        let mut input = std::io::BufReader::new(stdin.lock());
+        input.read_to_end(&mut buffer).unwrap();
+    } else {
+        let mut file =
+            std::fs::File::open(file_name).expect("file not found");
+        file.read_to_end(&mut buffer).unwrap();
+    }
+    let stdout = std::io::stdout();
+    let mut output = std::io::BufWriter::new(stdout.lock());
 
+    let max_order = options.max_order;
+    let processed_bytes = buffer.len().min(MAX_PROCESSED_BYTES);
+    let max_window_size = match options.max_window_size_opt {
+        Some(max_window_size) => {
+            if max_window_size < processed_bytes &&
+                history_source_type != "tree" {
+                exit_with_usage(&format!(
+                    "window can't slide with {} history source, it must hold \
+                     {} processed bytes", history_source_type,
+                    processed_bytes));
+            }
+            max_window_size
+        }
+        None => processed_bytes.max(1),
+    };
     match history_source_type {
-        "brute_force" =>
-            print_bit_histories::<NaiveHistorySource>(&buffer),
-        "fat_map" =>
-            print_bit_histories::<FatMapHistorySource>(&buffer),
-        "tree" =>
-            print_bit_histories::<TreeHistorySource>(&buffer),
+        "brute_force" => print_bit_histories::<NaiveHistorySource, _>(
+            &buffer, max_window_size, max_order, &mut output),
+        "fat_map" => print_bit_histories::<FatMapHistorySource, _>(
+            &buffer, max_window_size, max_order, &mut output),
+        "tree" => print_bit_histories::<TreeHistorySource, _>(
+            &buffer, max_window_size, max_order, &mut output),
         _ =>
             panic!("unrecognized history source type!")
+    }.and_then(|_| output.flush()).expect("failed to write output");
+}
+
+struct Options {
+    history_source_type: String,
+    file_name: String,
+    max_order: usize,
+    max_window_size_opt: Option<usize>,
+}
+
+fn parse_options<Args: Iterator<Item=String>>(mut args: Args) -> Options {
+    let mut positional = Vec::new();
+    let mut max_order = MAX_ORDER;
+    let mut max_window_size_opt = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--order" => {
+                max_order = parse_flag_value(&arg, args.next());
+                if max_order > MAX_ORDER {
+                    exit_with_usage(&format!("order {} exceeds maximum of {}",
+                                             max_order, MAX_ORDER));
+                }
+            }
+            "--window" => {
+                let max_window_size = parse_flag_value(&arg, args.next());
+                if max_window_size == 0 {
+                    exit_with_usage("window size must be positive");
+                }
+                max_window_size_opt = Some(max_window_size);
+            }
+            _ if arg.starts_with("--") =>
+                exit_with_usage(&format!("unknown flag {}", arg)),
+            _ =>
+                positional.push(arg),
+        }
+    }
+    if let Some(max_window_size) = max_window_size_opt {
+        if max_order >= max_window_size {
+            exit_with_usage(&format!(
+                "order {} must be less than window size {}",
+                max_order, max_window_size));
+        }
+    }
+    if positional.len() != 2 {
+        exit_with_usage("expected history source type and file name");
+    }
+    let file_name = positional.pop().unwrap();
+    let history_source_type = positional.pop().unwrap();
+    Options {
+        history_source_type,
+        file_name,
+        max_order,
+        max_window_size_opt,
     }
 }
 
+fn parse_flag_value(flag: &str, value_opt: Option<String>) -> usize {
+    match value_opt.as_ref().map(|value| value.parse()) {
+        Some(Ok(value)) => value,
+        _ => exit_with_usage(&format!("{} requires a number", flag)),
+    }
+}
+
+fn exit_with_usage(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("usage: demixer [--order N] [--window BYTES] \
+               (brute_force|fat_map|tree) <file or - for stdin>");
+    std::process::exit(1)
+}
+
 fn print_banner() {
     eprintln!("demixer - file compressor aimed at high compression ratios");
     eprint!("Copyright (C) 2018  Piotr Tarsa ");
@@ -62,29 +153,34 @@ fn print_banner() {
     eprintln!();
 }
 
-fn print_bit_histories<Source: HistorySource>(input: &[u8]) {
+fn print_bit_histories<Source: HistorySource, W: Write>(
+    input: &[u8], max_window_size: usize, max_order: usize, output: &mut W)
+    -> std::io::Result<()> {
     let mut collected_states =
-        CollectedContextStates::new(MAX_ORDER);
+        CollectedContextStates::new(max_order);
     let mut history_source =
-        Source::new(input.len(), MAX_ORDER);
-    for (i, &x) in input.iter().take(1234).enumerate() {
-        println!("Processing byte with index: {}, {}", i, x as char);
+        Source::new(max_window_size, max_order);
+    for (i, &x) in input.iter().take(MAX_PROCESSED_BYTES).enumerate() {
+        writeln!(output, "Processing byte with index: {}, {}", i, x as char)?;
         history_source.start_new_byte();
         for bit_index in (0..7 + 1).rev() {
             collected_states.reset();
             history_source.gather_history_states(&mut collected_states);
-            if collected_states.items().len() > 0 {
-                print!("{}: ", bit_index);
-                print!("{:x}", collected_states.items()[0].bit_history);
+            if !collected_states.items().is_empty() {
+                write!(output, "{}: ", bit_index)?;
+                write!(output, "{:x}",
+                       collected_states.items()[0].bit_history)?;
                 for i in 1..collected_states.items().len() {
-                    print!(", ");
-                    print!("{:x}", collected_states.items()[i].bit_history);
+                    write!(output, ", ")?;
+                    write!(output, "{:x}",
+                           collected_states.items()[i].bit_history)?;
                 }
-                println!();
+                writeln!(output)?;
             }
             let incoming_bit = get_bit(x, bit_index);
             history_source.process_input_bit(incoming_bit);
         }
-        println!();
+        writeln!(output)?;
     }
+    Ok(())
 }
@@ -0,0 +1,235 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// Source of reproducible pseudo-random numbers
+pub trait Prng {
+    fn new_by_scalar_seed(seed: u64) -> Self where Self: Sized;
+
+    fn next_int64(&mut self) -> u64;
+
+    /// Generates a real number from [0, 1) interval
+    fn next_real2(&mut self) -> f64 {
+        (self.next_int64() >> 11) as f64 * (1.0 / 9_007_199_254_740_992.0)
+    }
+
+    /// Generates an integer from [0, bound) interval without modulo bias
+    fn next_below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0);
+        // outputs below threshold would make lower results more frequent
+        let threshold = bound.wrapping_neg() % bound;
+        loop {
+            let value = self.next_int64();
+            if value >= threshold {
+                return value % bound;
+            }
+        }
+    }
+
+    /// Fisher-Yates shuffle
+    fn shuffle<T>(&mut self, items: &mut [T]) where Self: Sized {
+        for index in (1..items.len()).rev() {
+            let other_index = self.next_below(index as u64 + 1) as usize;
+            items.swap(index, other_index);
+        }
+    }
+}
+
+/// 64-bit Mersenne Twister (MT19937-64), ported from the reference
+/// implementation by Takuji Nishimura and Makoto Matsumoto
+#[derive(Clone)]
+pub struct MersenneTwister {
+    mt: [u64; MersenneTwister::NN],
+    index: usize,
+}
+
+impl MersenneTwister {
+    pub const NN: usize = 312;
+    const MM: usize = 156;
+    const MATRIX_A: u64 = 0xB502_6F5A_A966_19E9;
+    const UPPER_MASK: u64 = 0xFFFF_FFFF_8000_0000;
+    const LOWER_MASK: u64 = 0x7FFF_FFFF;
+
+    pub const DEFAULT_SEED: u64 = 5489;
+
+    pub fn new_by_scalar_seed(seed: u64) -> MersenneTwister {
+        let mut mt = [0u64; MersenneTwister::NN];
+        mt[0] = seed;
+        for i in 1..MersenneTwister::NN {
+            mt[i] = 6_364_136_223_846_793_005u64
+                .wrapping_mul(mt[i - 1] ^ (mt[i - 1] >> 62))
+                .wrapping_add(i as u64);
+        }
+        MersenneTwister { mt, index: MersenneTwister::NN }
+    }
+
+    pub fn new_by_array_seed(seed: &[u64]) -> MersenneTwister {
+        assert!(!seed.is_empty());
+        let nn = MersenneTwister::NN;
+        let mut result = MersenneTwister::new_by_scalar_seed(19_650_218);
+        let mt = &mut result.mt;
+        let mut i = 1;
+        let mut j = 0;
+        for _ in 0..nn.max(seed.len()) {
+            mt[i] = (mt[i] ^ (mt[i - 1] ^ (mt[i - 1] >> 62))
+                .wrapping_mul(3_935_559_000_370_003_845))
+                .wrapping_add(seed[j]).wrapping_add(j as u64);
+            i += 1;
+            j += 1;
+            if i >= nn {
+                mt[0] = mt[nn - 1];
+                i = 1;
+            }
+            if j >= seed.len() {
+                j = 0;
+            }
+        }
+        for _ in 0..nn - 1 {
+            mt[i] = (mt[i] ^ (mt[i - 1] ^ (mt[i - 1] >> 62))
+                .wrapping_mul(2_862_933_555_777_941_757))
+                .wrapping_sub(i as u64);
+            i += 1;
+            if i >= nn {
+                mt[0] = mt[nn - 1];
+                i = 1;
+            }
+        }
+        mt[0] = 1 << 63;
+        result
+    }
+
+    /// Restores a generator from the output of `state`
+    pub fn from_state(mt: [u64; MersenneTwister::NN], index: usize)
+                      -> MersenneTwister {
+        assert!(index <= MersenneTwister::NN);
+        MersenneTwister { mt, index }
+    }
+
+    /// Returns internal words and the index of the next word to be tempered
+    pub fn state(&self) -> ([u64; MersenneTwister::NN], usize) {
+        (self.mt, self.index)
+    }
+
+    pub fn next_int64(&mut self) -> u64 {
+        if self.index >= MersenneTwister::NN {
+            self.regenerate();
+        }
+        let mut x = self.mt[self.index];
+        self.index += 1;
+        x ^= (x >> 29) & 0x5555_5555_5555_5555;
+        x ^= (x << 17) & 0x71D6_7FFF_EDA6_0000;
+        x ^= (x << 37) & 0xFFF7_EEE0_0000_0000;
+        x ^= x >> 43;
+        x
+    }
+
+    fn regenerate(&mut self) {
+        let nn = MersenneTwister::NN;
+        let mm = MersenneTwister::MM;
+        let mt = &mut self.mt;
+        let twist = |upper: u64, lower: u64| {
+            let x = (upper & MersenneTwister::UPPER_MASK) |
+                (lower & MersenneTwister::LOWER_MASK);
+            (x >> 1) ^ ((x & 1) * MersenneTwister::MATRIX_A)
+        };
+        for i in 0..nn - mm {
+            mt[i] = mt[i + mm] ^ twist(mt[i], mt[i + 1]);
+        }
+        for i in nn - mm..nn - 1 {
+            mt[i] = mt[i + mm - nn] ^ twist(mt[i], mt[i + 1]);
+        }
+        mt[nn - 1] = mt[mm - 1] ^ twist(mt[nn - 1], mt[0]);
+        self.index = 0;
+    }
+}
+
+impl Default for MersenneTwister {
+    fn default() -> MersenneTwister {
+        MersenneTwister::new_by_scalar_seed(MersenneTwister::DEFAULT_SEED)
+    }
+}
+
+impl Prng for MersenneTwister {
+    fn new_by_scalar_seed(seed: u64) -> MersenneTwister {
+        MersenneTwister::new_by_scalar_seed(seed)
+    }
+
+    fn next_int64(&mut self) -> u64 {
+        MersenneTwister::next_int64(self)
+    }
+}
+
+/// xorshift128+ by Sebastiano Vigna, with shifts 23, 18, 5. Much smaller
+/// state and faster seeding than Mersenne Twister
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Xorshift128Plus {
+    state: [u64; 2],
+}
+
+impl Xorshift128Plus {
+    pub const DEFAULT_SEED: u64 = 5489;
+
+    /** Expands the seed with SplitMix64, as recommended by the author */
+    pub fn new_by_scalar_seed(seed: u64) -> Xorshift128Plus {
+        let mut splitmix_state = seed;
+        let mut splitmix_next = || {
+            splitmix_state = splitmix_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = splitmix_state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+        let first = splitmix_next();
+        let second = splitmix_next();
+        Xorshift128Plus::from_state([first, second])
+    }
+
+    pub fn from_state(state: [u64; 2]) -> Xorshift128Plus {
+        assert!(state != [0, 0], "state must not be all zeros");
+        Xorshift128Plus { state }
+    }
+
+    pub fn state(&self) -> [u64; 2] {
+        self.state
+    }
+
+    pub fn next_int64(&mut self) -> u64 {
+        let mut s1 = self.state[0];
+        let s0 = self.state[1];
+        let result = s0.wrapping_add(s1);
+        self.state[0] = s0;
+        s1 ^= s1 << 23;
+        self.state[1] = s1 ^ s0 ^ (s1 >> 18) ^ (s0 >> 5);
+        result
+    }
+}
+
+impl Default for Xorshift128Plus {
+    fn default() -> Xorshift128Plus {
+        Xorshift128Plus::new_by_scalar_seed(Xorshift128Plus::DEFAULT_SEED)
+    }
+}
+
+impl Prng for Xorshift128Plus {
+    fn new_by_scalar_seed(seed: u64) -> Xorshift128Plus {
+        Xorshift128Plus::new_by_scalar_seed(seed)
+    }
+
+    fn next_int64(&mut self) -> u64 {
+        Xorshift128Plus::next_int64(self)
+    }
+}
@@ -0,0 +1,104 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+use core::hash::Hasher;
+use std::collections::hash_map::DefaultHasher;
+
+pub trait ContextHasher: Default {
+    /** Hashes full context bytes together with the already known high bits
+     of the unfinished byte (prefixed with a leading one bit) */
+    fn hash(&self, context_bytes: &[u8], unfinished_byte_prefix: u32) -> u64;
+}
+
+/// SipHash with fixed keys from the standard library
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultContextHasher;
+
+impl ContextHasher for DefaultContextHasher {
+    fn hash(&self, context_bytes: &[u8], unfinished_byte_prefix: u32) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(context_bytes);
+        hasher.write_u32(unfinished_byte_prefix);
+        hasher.finish()
+    }
+}
+
+/// Fast multiplicative hash keeping only the highest `hash_bits` bits
+#[derive(Clone, Copy, Debug)]
+pub struct MultiplyShiftContextHasher {
+    hash_bits: u32,
+}
+
+impl MultiplyShiftContextHasher {
+    const MULTIPLIER: u64 = 0x9E37_79B9_7F4A_7C15;
+
+    pub fn new(hash_bits: u32) -> MultiplyShiftContextHasher {
+        assert!(hash_bits > 0 && hash_bits <= 64);
+        MultiplyShiftContextHasher { hash_bits }
+    }
+}
+
+impl Default for MultiplyShiftContextHasher {
+    fn default() -> MultiplyShiftContextHasher {
+        MultiplyShiftContextHasher::new(64)
+    }
+}
+
+impl ContextHasher for MultiplyShiftContextHasher {
+    fn hash(&self, context_bytes: &[u8], unfinished_byte_prefix: u32) -> u64 {
+        let mut hash = (unfinished_byte_prefix as u64)
+            .wrapping_mul(MultiplyShiftContextHasher::MULTIPLIER);
+        for &byte in context_bytes.iter() {
+            hash = (hash ^ byte as u64)
+                .wrapping_mul(MultiplyShiftContextHasher::MULTIPLIER);
+            hash ^= hash >> 29;
+        }
+        hash >> (64 - self.hash_bits)
+    }
+}
+
+/// 64-bit FNV-1a checksum computed incrementally over a byte stream
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Fnv1aChecksum {
+    state: u64,
+}
+
+impl Fnv1aChecksum {
+    const OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01B3;
+
+    pub fn new() -> Fnv1aChecksum {
+        Fnv1aChecksum { state: Fnv1aChecksum::OFFSET_BASIS }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes.iter() {
+            self.state = (self.state ^ byte as u64)
+                .wrapping_mul(Fnv1aChecksum::PRIME);
+        }
+    }
+
+    pub fn value(&self) -> u64 {
+        self.state
+    }
+}
+
+impl Default for Fnv1aChecksum {
+    fn default() -> Fnv1aChecksum {
+        Fnv1aChecksum::new()
+    }
+}
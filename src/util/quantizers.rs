@@ -0,0 +1,28 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+/// Number of distinct values returned by `quantize_distance`
+pub const DISTANCE_BUCKETS_COUNT: usize = 32;
+
+/// Maps a distance to a logarithmic bucket, i.e. to the number of
+/// significant bits in the distance, capped so the result stays below
+/// `DISTANCE_BUCKETS_COUNT`
+pub fn quantize_distance(distance: usize) -> u8 {
+    let significant_bits = (usize::BITS - distance.leading_zeros()) as usize;
+    significant_bits.min(DISTANCE_BUCKETS_COUNT - 1) as u8
+}
@@ -0,0 +1,86 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+use std::io;
+
+use demixer::coding::bit_io::{BitReader, BitWriter};
+use demixer::random::MersenneTwister;
+
+#[test]
+fn single_bits_round_trip_for_any_length() {
+    let mut prng = MersenneTwister::default();
+    for length in 0usize..100 {
+        let bits: Vec<bool> =
+            (0..length).map(|_| (prng.next_int64() & 1) == 1).collect();
+        let mut writer = BitWriter::new(Vec::new());
+        for &bit in bits.iter() {
+            writer.write_bit(bit).unwrap();
+        }
+        let encoded = writer.finish().unwrap();
+        assert_eq!(encoded.len(), length.div_ceil(8));
+
+        let mut reader = BitReader::new(&encoded[..]);
+        for &bit in bits.iter() {
+            assert_eq!(reader.read_bit().unwrap(), bit);
+        }
+        if length % 8 == 0 {
+            assert_eq!(reader.read_bit().unwrap_err().kind(),
+                       io::ErrorKind::UnexpectedEof);
+        }
+    }
+}
+
+#[test]
+fn mixed_width_writes_round_trip() {
+    let mut prng = MersenneTwister::new_by_scalar_seed(7);
+    for _ in 0..50 {
+        let items: Vec<(u64, u8)> = (0..20).map(|_| {
+            let bits_count = (prng.next_int64() % 65) as u8;
+            let value = if bits_count == 0 { 0 } else {
+                prng.next_int64() >> (64 - bits_count as u32)
+            };
+            (value, bits_count)
+        }).collect();
+        let mut writer = BitWriter::new(Vec::new());
+        for &(value, bits_count) in items.iter() {
+            writer.write_bits(value, bits_count).unwrap();
+            writer.write_byte(value as u8).unwrap();
+        }
+        let encoded = writer.finish().unwrap();
+
+        let mut reader = BitReader::new(&encoded[..]);
+        for &(value, bits_count) in items.iter() {
+            assert_eq!(reader.read_bits(bits_count).unwrap(), value);
+            assert_eq!(reader.read_byte().unwrap(), value as u8);
+        }
+    }
+}
+
+#[test]
+fn partial_byte_is_padded_with_zeros() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(0b101, 3).unwrap();
+    assert_eq!(writer.finish().unwrap(), vec![0b1010_0000]);
+
+    let input = [0b1100_0000, 0xab];
+    let mut reader = BitReader::new(&input[..]);
+    assert_eq!(reader.read_bits(2).unwrap(), 0b11);
+    reader.align_to_byte();
+    assert_eq!(reader.read_byte().unwrap(), 0xab);
+}
@@ -0,0 +1,60 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+use demixer::random::MersenneTwister;
+use demixer::util::hash::Fnv1aChecksum;
+
+fn checksum(bytes: &[u8]) -> u64 {
+    let mut checksum = Fnv1aChecksum::new();
+    checksum.update(bytes);
+    checksum.value()
+}
+
+#[test]
+fn matches_reference_values() {
+    assert_eq!(checksum(b""), 0xCBF2_9CE4_8422_2325);
+    assert_eq!(checksum(b"a"), 0xAF63_DC4C_8601_EC8C);
+    assert_eq!(checksum(b"foobar"), 0x8594_4171_F739_67E8);
+}
+
+#[test]
+fn doesnt_depend_on_chunking() {
+    let mut prng = MersenneTwister::default();
+    let input: Vec<u8> =
+        (0..10_000).map(|_| prng.next_int64() as u8).collect();
+    let expected = checksum(&input);
+    for &chunk_size in [1, 7, 4096].iter() {
+        let mut chunked = Fnv1aChecksum::new();
+        for chunk in input.chunks(chunk_size) {
+            chunked.update(chunk);
+        }
+        assert_eq!(chunked.value(), expected, "chunk size = {}", chunk_size);
+    }
+}
+
+#[test]
+fn detects_single_byte_change() {
+    let mut input = b"guards against silent corruption".to_vec();
+    let original = checksum(&input);
+    for index in 0..input.len() {
+        input[index] ^= 1;
+        assert_ne!(checksum(&input), original, "index = {}", index);
+        input[index] ^= 1;
+    }
+}
@@ -24,16 +24,39 @@ use demixer::history::{
 };
 use demixer::history::naive::NaiveHistorySource;
 use demixer::history::fat_map::FatMapHistorySource;
-use demixer::history::tree::TreeHistorySource;
+use demixer::history::tree::{NodesAllocation, TreeHistorySource};
+use demixer::random::Prng;
+
+/** Random symbols interleaved with copies of earlier fragments, which
+ * create contexts deeper than max order */
+// u64::is_multiple_of suggested by clippy needs a much newer toolchain
+#[allow(unknown_lints, clippy::manual_is_multiple_of)]
+pub fn make_input_with_copies<P: Prng>(prng: &mut P, alphabet_size: u64,
+                                       length: usize) -> Vec<u8> {
+    let mut input: Vec<u8> = Vec::new();
+    while input.len() < length {
+        if input.len() > 100 && prng.next_int64() % 20 == 0 {
+            let start = (prng.next_int64() as usize) % (input.len() - 80);
+            let copied = input[start..start + 80].to_vec();
+            input.extend_from_slice(&copied);
+        } else {
+            input.push(b'a' + (prng.next_int64() % alphabet_size) as u8);
+        }
+    }
+    input
+}
 
 pub fn compare_for_input(input: &[u8], max_order: usize, run_naive: bool) {
     let mut naive_source = NaiveHistorySource::new(input.len(), max_order);
     let mut fat_map_source = FatMapHistorySource::new(input.len(), max_order);
     let mut tree_source = TreeHistorySource::new(input.len(), max_order);
+    let mut bump_tree_source = TreeHistorySource::new_with_allocation(
+        input.len(), max_order, NodesAllocation::BumpOnly);
 
     let mut naive_source_results = CollectedContextStates::new(max_order);
     let mut fat_map_source_results = CollectedContextStates::new(max_order);
     let mut tree_source_results = CollectedContextStates::new(max_order);
+    let mut bump_tree_source_results = CollectedContextStates::new(max_order);
 
     for (index, byte) in input.iter().enumerate() {
         tree_source.tree.check_integrity(max_order);
@@ -42,6 +65,7 @@ pub fn compare_for_input(input: &[u8], max_order: usize, run_naive: bool) {
         }
         fat_map_source.start_new_byte();
         tree_source.start_new_byte();
+        bump_tree_source.start_new_byte();
         if PRINT_DEBUG {
             println!("started byte #{}, max order = {}", index, max_order);
             tree_source.tree.print();
@@ -59,6 +83,10 @@ pub fn compare_for_input(input: &[u8], max_order: usize, run_naive: bool) {
             tree_source_results.reset();
             tree_source.gather_history_states(&mut tree_source_results);
 
+            bump_tree_source_results.reset();
+            bump_tree_source.gather_history_states(
+                &mut bump_tree_source_results);
+
             if run_naive {
                 assert_eq!(naive_source_results.items(),
                            fat_map_source_results.items(),
@@ -74,6 +102,10 @@ pub fn compare_for_input(input: &[u8], max_order: usize, run_naive: bool) {
                        tree_source_results.items(),
                        "index = {}, bit index = {}, input = {:?}",
                        index, bit_index, input);
+            assert_eq!(tree_source_results.items(),
+                       bump_tree_source_results.items(),
+                       "index = {}, bit index = {}, input = {:?}",
+                       index, bit_index, input);
 
             let input_bit = (byte & (1 << bit_index)) != 0;
             if PRINT_DEBUG { println!("processing bit: {}", input_bit); }
@@ -82,6 +114,7 @@ pub fn compare_for_input(input: &[u8], max_order: usize, run_naive: bool) {
             }
             fat_map_source.process_input_bit(input_bit);
             tree_source.process_input_bit(input_bit);
+            bump_tree_source.process_input_bit(input_bit);
             if PRINT_DEBUG { println!(); }
         }
     }
@@ -19,8 +19,9 @@ extern crate demixer;
 
 mod compare_history_sources;
 
-use compare_history_sources::compare_for_input;
+use compare_history_sources::{compare_for_input, make_input_with_copies};
 use demixer::MAX_ORDER;
+use demixer::random::MersenneTwister;
 
 #[test]
 #[cfg(not(feature = "long_tests"))]
@@ -28,6 +29,7 @@ use demixer::MAX_ORDER;
 fn long_tests_skipped() {
     // silencing dead code and unused imports warnings
     compare_for_input(&[], 0, false);
+    make_input_with_copies(&mut MersenneTwister::default(), 1, 0);
     assert_eq!(MAX_ORDER, 0);
 }
 
@@ -50,11 +52,31 @@ fn compare_for_repeated_byte_input() {
     }
 }
 
+#[test]
+#[cfg(feature = "long_tests")]
+fn compare_for_random_inputs_with_copies() {
+    let mut prng = MersenneTwister::default();
+    for &alphabet_size in [2, 3, 16].iter() {
+        let input = make_input_with_copies(&mut prng, alphabet_size, 1000);
+        for &max_order in [0, 1, 2, 3, 5, 8, 13, 20, 40, MAX_ORDER].iter() {
+            compare_for_input(&input, max_order, false);
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "long_tests")]
+fn compare_for_long_run() {
+    for &max_order in [0, 5, MAX_ORDER].iter() {
+        compare_for_input(&[0; 10000], max_order, false);
+    }
+}
+
 #[test]
 #[cfg(feature = "long_tests")]
 fn compare_for_two_symbols_sequences() {
     let symbols_pairs: &[(u8, u8)] =
-        &[(0, 255), ('b' as u8, 'a' as u8), (215, 15), (31, 32)];
+        &[(0, 255), (b'b', b'a'), (215, 15), (31, 32)];
     for &(sym_0, sym_1) in symbols_pairs.iter() {
         // regularly interrupted runs
         {
@@ -89,7 +111,7 @@ fn compare_for_two_symbols_sequences() {
 #[test]
 #[cfg(feature = "long_tests")]
 fn compare_for_multi_symbol_sequences() {
-    for &starting_symbol in [0 as u8, 'a' as u8].iter() {
+    for &starting_symbol in [0u8, b'a'].iter() {
         let mut word = vec![starting_symbol];
         let mut next_symbol = starting_symbol + 1;
         while word.len() < 300 {
@@ -108,7 +130,7 @@ fn compare_for_multi_symbol_sequences() {
 #[cfg(feature = "long_tests")]
 fn compare_for_repeated_byte_borders() {
     let border_and_middle_starter_symbols: &[(u8, u8)] =
-        &[(0, 128), ('z' as u8, 'a' as u8), (215, 15), (31, 32)];
+        &[(0, 128), (b'z', b'a'), (215, 15), (31, 32)];
     for &(border_sym, middle_sym) in border_and_middle_starter_symbols.iter() {
         let mut middle = vec![middle_sym];
         let mut next_symbol: u8 = middle_sym + 1;
@@ -136,7 +158,7 @@ fn compare_for_repeated_byte_borders() {
 #[cfg(feature = "long_tests")]
 fn compare_for_repeated_pattern_borders() {
     let border_and_middle_starter_symbols: &[(u8, u8, u8)] =
-        &[(0, 255, 128), ('z' as u8, 'v' as u8, 'a' as u8), (31, 32, 215)];
+        &[(0, 255, 128), (b'z', b'v', b'a'), (31, 32, 215)];
     for &(border_sym_0, border_sym_1, middle_sym)
         in border_and_middle_starter_symbols.iter() {
         let mut middle = vec![middle_sym];
@@ -19,8 +19,9 @@ extern crate demixer;
 
 mod compare_history_sources;
 
-use compare_history_sources::compare_for_input;
+use compare_history_sources::{compare_for_input, make_input_with_copies};
 use demixer::MAX_ORDER;
+use demixer::random::{MersenneTwister, Prng, Xorshift128Plus};
 
 #[test]
 fn compare_for_one_byte_input() {
@@ -42,15 +43,15 @@ fn compare_for_one_byte_input() {
 fn compare_for_repeated_byte_input() {
     for max_order in 0..MAX_ORDER + 1 {
         compare_for_input(&[0xb5; 1], max_order, true);
-        compare_for_input(&[' ' as u8; 2], max_order, true);
-        compare_for_input(&['a' as u8; 5], max_order, true);
+        compare_for_input(&[b' '; 2], max_order, true);
+        compare_for_input(&[b'a'; 5], max_order, true);
     }
 }
 
 #[test]
 fn compare_for_two_symbols_sequences() {
     let symbols_pairs: &[(u8, u8)] =
-        &[(0, 255), ('b' as u8, 'a' as u8), (215, 15), (31, 32)];
+        &[(0, 255), (b'b', b'a'), (215, 15), (31, 32)];
     for &(sym_0, sym_1) in symbols_pairs.iter() {
         // regularly interrupted runs
         {
@@ -84,7 +85,7 @@ fn compare_for_two_symbols_sequences() {
 
 #[test]
 fn compare_for_multi_symbol_sequences() {
-    for &starting_symbol in [0 as u8, 'a' as u8].iter() {
+    for &starting_symbol in [0u8, b'a'].iter() {
         let mut word = vec![starting_symbol];
         let mut next_symbol = starting_symbol + 1;
         while word.len() < 20 {
@@ -102,7 +103,7 @@ fn compare_for_multi_symbol_sequences() {
 #[test]
 fn compare_for_repeated_byte_borders() {
     let border_and_middle_starter_symbols: &[(u8, u8)] =
-        &[(0, 128), ('z' as u8, 'a' as u8), (215, 15), (31, 32)];
+        &[(0, 128), (b'z', b'a'), (215, 15), (31, 32)];
     for &(border_sym, middle_sym) in border_and_middle_starter_symbols.iter() {
         let mut middle = vec![middle_sym];
         let mut next_symbol: u8 = middle_sym + 1;
@@ -129,7 +130,7 @@ fn compare_for_repeated_byte_borders() {
 #[test]
 fn compare_for_repeated_pattern_borders() {
     let border_and_middle_starter_symbols: &[(u8, u8, u8)] =
-        &[(0, 255, 128), ('z' as u8, 'v' as u8, 'a' as u8), (31, 32, 215)];
+        &[(0, 255, 128), (b'z', b'v', b'a'), (31, 32, 215)];
     for &(border_sym_0, border_sym_1, middle_sym)
         in border_and_middle_starter_symbols.iter() {
         let mut middle = vec![middle_sym];
@@ -163,3 +164,21 @@ fn compare_for_repeated_pattern_borders() {
         assert!(middle.len() >= 10);
     }
 }
+
+fn compare_for_random_inputs_at_high_orders_with<P: Prng>(prng: &mut P) {
+    for &alphabet_size in [2, 4].iter() {
+        let input = make_input_with_copies(prng, alphabet_size, 300);
+        for &max_order in [20, 40, MAX_ORDER].iter() {
+            compare_for_input(&input, max_order, true);
+        }
+    }
+}
+
+#[test]
+fn compare_for_random_inputs_at_high_orders() {
+    compare_for_random_inputs_at_high_orders_with(
+        &mut MersenneTwister::default());
+    compare_for_random_inputs_at_high_orders_with(
+        &mut Xorshift128Plus::default());
+}
+
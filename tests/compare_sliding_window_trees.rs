@@ -28,16 +28,17 @@ use demixer::history::tree::{
     TreeHistorySource,
     TreeState,
 };
+use demixer::random::MersenneTwister;
 
 #[test]
 fn compare_for_repeated_byte_input() {
-    let lengths_of_inputs_and_prefixes = vec![
+    let lengths_of_inputs_and_prefixes = [
         (0, 7, 13),
         (2, 0, 13),
         (1, 13, 28),
         (22, 54, 83),
     ];
-    for &byte in [0xb5, ' ' as u8, 'a' as u8].iter() {
+    for &byte in [0xb5, b' ', b'a'].iter() {
         for &(prefix_1_length, prefix_2_length, common_input_length) in
             lengths_of_inputs_and_prefixes.iter() {
             let prefix_1 = vec![byte; prefix_1_length];
@@ -58,8 +59,8 @@ fn compare_for_repeated_byte_input() {
 #[test]
 fn compare_for_two_symbols_interrupted_runs() {
     let symbols_pairs: &[(u8, u8)] =
-        &[(0, 255), ('b' as u8, 'a' as u8), (215, 15), (31, 32)];
-    let lengths_of_inputs_and_prefixes = vec![
+        &[(0, 255), (b'b', b'a'), (215, 15), (31, 32)];
+    let lengths_of_inputs_and_prefixes = [
         (0, 7, 13),
         (2, 0, 13),
         (1, 13, 28),
@@ -94,8 +95,8 @@ fn compare_for_two_symbols_interrupted_runs() {
 #[test]
 fn compare_for_two_symbols_fibonacci_word() {
     let symbols_pairs: &[(u8, u8)] =
-        &[(0, 255), ('b' as u8, 'a' as u8), (215, 15), (31, 32)];
-    let lengths_of_inputs_and_prefixes = vec![
+        &[(0, 255), (b'b', b'a'), (215, 15), (31, 32)];
+    let lengths_of_inputs_and_prefixes = [
         (0, 7, 13),
         (2, 0, 13),
         (1, 13, 28),
@@ -128,13 +129,13 @@ fn compare_for_two_symbols_fibonacci_word() {
 
 #[test]
 fn compare_for_multi_symbol_sequences() {
-    let lengths_of_inputs_and_prefixes = vec![
+    let lengths_of_inputs_and_prefixes = [
         (0, 7, 13),
         (2, 0, 13),
         (1, 13, 28),
         (22, 54, 83),
     ];
-    for &starting_symbol in [0 as u8, 'a' as u8].iter() {
+    for &starting_symbol in [0u8, b'a'].iter() {
         let mut input = vec![starting_symbol];
         let mut next_symbol = starting_symbol + 1;
         while input.len() < 300 {
@@ -162,14 +163,14 @@ fn compare_for_multi_symbol_sequences() {
 
 #[test]
 fn compare_for_repeated_byte_borders() {
-    let lengths_of_prefixes_and_suffixes = vec![
+    let lengths_of_prefixes_and_suffixes = [
         (1, 7, 13),
         (2, 1, 13),
         (20, 13, 28),
         (22, 54, 83),
     ];
     let border_and_middle_starter_symbols: &[(u8, u8)] =
-        &[(0, 128), ('z' as u8, 'a' as u8), (215, 15), (31, 32)];
+        &[(0, 128), (b'z', b'a'), (215, 15), (31, 32)];
     for &(border_sym, middle_sym) in border_and_middle_starter_symbols.iter() {
         let mut middle = vec![middle_sym];
         let mut next_symbol: u8 = middle_sym + 1;
@@ -201,14 +202,14 @@ fn compare_for_repeated_byte_borders() {
 
 #[test]
 fn compare_for_repeated_pattern_borders() {
-    let lengths_of_prefixes_and_suffixes = vec![
+    let lengths_of_prefixes_and_suffixes = [
         (1, 7, 13),
         (2, 1, 13),
         (20, 13, 28),
         (22, 54, 83),
     ];
     let border_and_middle_starter_symbols: &[(u8, u8, u8)] =
-        &[(0, 255, 128), ('z' as u8, 'v' as u8, 'a' as u8), (31, 32, 215)];
+        &[(0, 255, 128), (b'z', b'v', b'a'), (31, 32, 215)];
     for &(border_sym_0, border_sym_1, middle_sym)
         in border_and_middle_starter_symbols.iter() {
         let mut middle = vec![middle_sym];
@@ -237,7 +238,7 @@ fn compare_for_repeated_pattern_borders() {
                     let max_window_size = middle_length + suffix_length;
                     for &max_order in [0, 1, 2, 3, 4, 7, 20, 40, 100]
                         .iter().take_while(|&&order| order < max_window_size) {
-                        compare_for_input(&prefix_1, &prefix_2, &common_input,
+                        compare_for_input(prefix_1, prefix_2, &common_input,
                                           max_window_size, max_order);
                     }
                 }
@@ -247,6 +248,35 @@ fn compare_for_repeated_pattern_borders() {
     }
 }
 
+#[test]
+fn compare_for_smallest_windows_and_highest_orders() {
+    let lengths_of_inputs_and_prefixes = [
+        (0, 7, 13),
+        (2, 0, 13),
+        (1, 13, 28),
+        (22, 54, 83),
+    ];
+    let mut prng = MersenneTwister::default();
+    for &alphabet_size in [1, 2, 3, 256].iter() {
+        let input: Vec<_> = (0..300)
+            .map(|_| (prng.next_int64() % alphabet_size) as u8).collect();
+        for &(prefix_1_length, prefix_2_length, common_input_length) in
+            lengths_of_inputs_and_prefixes.iter() {
+            let prefix_1 = &input[..prefix_1_length];
+            let prefix_2 = &input[..prefix_2_length];
+            let common_input = &input[..common_input_length];
+            for &max_window_size in [1, 2, 3, 5, 8, 12, 41].iter()
+                .take_while(|&&size| size < common_input_length) {
+                // order equal to window size - 1 is the highest allowed
+                for &max_order in [0, max_window_size - 1].iter() {
+                    compare_for_input(prefix_1, prefix_2, common_input,
+                                      max_window_size, max_order);
+                }
+            }
+        }
+    }
+}
+
 fn compare_for_input(prefix_1: &[u8], prefix_2: &[u8], common: &[u8],
                      max_window_size: usize, max_order: usize) {
     assert!(max_order < max_window_size);
@@ -0,0 +1,70 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+mod feeding;
+
+use feeding::feed;
+use demixer::history::HistorySource;
+use demixer::history::tree::{Tree, TreeHistorySource};
+use demixer::random::MersenneTwister;
+
+fn live_nodes_count_after_feeding(input: &[u8], max_order: usize) -> usize {
+    let mut source = TreeHistorySource::new(input.len(), max_order);
+    feed(&mut source, input);
+    source.tree.nodes().live_nodes_count()
+}
+
+#[test]
+fn counts_for_small_strings() {
+    // 'a' and 'b' differ on the 7th bit, nothing else branches
+    assert_eq!(Tree::count_distinct_suffixes(b"ab", 5), 1);
+    // every suffix is a prefix of a longer one
+    assert_eq!(Tree::count_distinct_suffixes(b"aaaa", 5), 0);
+    assert_eq!(Tree::count_distinct_suffixes(b"", 5), 0);
+    // sorted suffixes: abac ac bac c, 'a' separates from 'b' and 'c' first,
+    // then 'b' from 'c' both at first byte and after 'a'
+    assert_eq!(Tree::count_distinct_suffixes(b"abac", 5), 3);
+    assert_eq!(Tree::count_distinct_suffixes(b"abac", 0), 2);
+    // sorted suffixes: a ana anana banana na nana, suffixes ending at input
+    // end are prefixes of the following ones, so only 'a', 'b' and 'n'
+    // branch
+    assert_eq!(Tree::count_distinct_suffixes(b"banana", 10), 2);
+}
+
+#[test]
+fn matches_online_tree() {
+    let mut prng = MersenneTwister::default();
+    for &alphabet_size in [2, 3, 16, 256].iter() {
+        let input: Vec<u8> = (0..1000)
+            .map(|_| (prng.next_int64() % alphabet_size) as u8).collect();
+        for &max_order in [0, 1, 2, 5, 20].iter() {
+            assert_eq!(Tree::count_distinct_suffixes(&input, max_order),
+                       live_nodes_count_after_feeding(&input, max_order),
+                       "alphabet size = {}, max order = {}",
+                       alphabet_size, max_order);
+        }
+    }
+    for input in [&b"banana"[..], b"mississippi", b"abracadabra"].iter() {
+        for max_order in 0..12 {
+            assert_eq!(Tree::count_distinct_suffixes(input, max_order),
+                       live_nodes_count_after_feeding(input, max_order),
+                       "input = {:?}, max order = {}", input, max_order);
+        }
+    }
+}
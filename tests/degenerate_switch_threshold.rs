@@ -0,0 +1,123 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+mod feeding;
+
+use feeding::feed_checked;
+use demixer::history::{
+    CollectedContextStates,
+    HistorySource,
+};
+use demixer::history::tree::{
+    NodesAllocation,
+    TreeHistorySource,
+    TreeState,
+};
+
+/** Run of 'a' bytes with 'b' bytes at given positions */
+fn make_mostly_constant_input(length: usize, anomalies: &[usize]) -> Vec<u8> {
+    let mut input = vec![b'a'; length];
+    for &position in anomalies.iter() {
+        input[position] = b'b';
+    }
+    input
+}
+
+fn new_source(max_window_size: usize, max_order: usize,
+              threshold: usize) -> TreeHistorySource {
+    TreeHistorySource::new_with_config(
+        max_window_size, max_order, NodesAllocation::FreeList, threshold,
+        false)
+}
+
+#[test]
+fn stays_degenerate_below_threshold() {
+    let max_order = 5;
+    let input = make_mostly_constant_input(1000, &[100, 400, 700]);
+    let mut delayed_source = new_source(input.len(), max_order, 4);
+    feed_checked(&mut delayed_source, &input, max_order);
+    assert!(delayed_source.tree.tree_state == TreeState::Degenerate);
+    assert_eq!(delayed_source.tree.nodes().live_nodes_count(), 0);
+
+    let mut eager_source = new_source(input.len(), max_order, 1);
+    feed_checked(&mut eager_source, &input, max_order);
+    assert!(eager_source.tree.tree_state == TreeState::Proper);
+    assert!(eager_source.tree.nodes().live_nodes_count() > 0);
+}
+
+#[test]
+fn matches_eager_tree_after_switch() {
+    let max_order = 5;
+    let input = make_mostly_constant_input(1000, &[100, 400, 700, 701, 950]);
+    let mut delayed_source = new_source(input.len(), max_order, 3);
+    let mut eager_source = new_source(input.len(), max_order, 1);
+    let mut delayed_results = CollectedContextStates::new(max_order);
+    let mut eager_results = CollectedContextStates::new(max_order);
+    for (index, &byte) in input.iter().enumerate() {
+        delayed_source.start_new_byte();
+        eager_source.start_new_byte();
+        for bit_index in (0..7 + 1).rev() {
+            if index > 700 {
+                delayed_source.gather_history_states(&mut delayed_results);
+                eager_source.gather_history_states(&mut eager_results);
+                assert_eq!(delayed_results.items(), eager_results.items(),
+                           "index = {}, bit index = {}", index, bit_index);
+            }
+            let input_bit = (byte & (1 << bit_index)) != 0;
+            delayed_source.process_input_bit(input_bit);
+            eager_source.process_input_bit(input_bit);
+        }
+    }
+    assert_eq!(delayed_source.tree.stats().delayed_degenerate_switches, 1);
+    assert_eq!(delayed_source.tree.nodes().live_nodes_count(),
+               eager_source.tree.nodes().live_nodes_count());
+}
+
+#[test]
+fn anomalies_sliding_out_of_window_are_forgotten() {
+    let max_order = 3;
+    let anomalies: Vec<usize> = (1..20).map(|index| index * 150).collect();
+    let input = make_mostly_constant_input(3000, &anomalies);
+    let mut source = new_source(100, max_order, 2);
+    feed_checked(&mut source, &input, max_order);
+    assert!(source.tree.tree_state == TreeState::Degenerate);
+    assert_eq!(source.tree.stats().delayed_degenerate_switches, 0);
+}
+
+#[test]
+fn switches_when_window_has_only_anomalies() {
+    let max_order = 1;
+    let mut source = new_source(2, max_order, 3);
+    feed_checked(&mut source, b"aabcdaaaa", max_order);
+    source.tree.check_integrity(max_order);
+    assert!(source.tree.stats().delayed_degenerate_switches > 0);
+}
+
+#[test]
+fn sliding_with_sparse_anomalies_keeps_tree_consistent() {
+    let max_order = 4;
+    let anomalies: Vec<usize> = (1..100).map(|index| index * 37 % 2000)
+        .collect();
+    let input = make_mostly_constant_input(2000, &anomalies);
+    for &threshold in [1, 2, 3, 5].iter() {
+        let mut source = new_source(120, max_order, threshold);
+        feed_checked(&mut source, &input, max_order);
+        source.tree.check_integrity(max_order);
+    }
+}
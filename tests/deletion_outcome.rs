@@ -0,0 +1,73 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+mod feeding;
+
+use feeding::feed;
+use demixer::history::HistorySource;
+use demixer::history::tree::{DeletionOutcome, TreeHistorySource};
+
+fn remove_all_suffixes(source: &mut TreeHistorySource, max_order: usize)
+                       -> Vec<DeletionOutcome> {
+    let mut outcomes = Vec::new();
+    while source.tree.window_size > 0 {
+        outcomes.push(source.tree.remove_leftmost_suffix(
+            &mut source.active_contexts));
+        source.tree.check_integrity(max_order);
+    }
+    outcomes
+}
+
+#[test]
+fn window_emptying_to_identical_bytes_makes_tree_degenerate() {
+    let max_order = 5;
+    let mut source = TreeHistorySource::new(10, max_order);
+    feed(&mut source, b"baaaa");
+    let outcomes = remove_all_suffixes(&mut source, max_order);
+    assert_eq!(outcomes[0], DeletionOutcome::RootToDegenerate);
+    assert!(outcomes[1..].iter()
+        .all(|&outcome| outcome == DeletionOutcome::InDegenerateState));
+}
+
+#[test]
+fn outcomes_match_stats() {
+    for input in [&b"abracadabra"[..], b"aabaabaaab", b"mississippi"].iter() {
+        for max_order in 0..5 {
+            let mut source = TreeHistorySource::new(input.len(), max_order);
+            feed(&mut source, input);
+            let outcomes = remove_all_suffixes(&mut source, max_order);
+            let count = |expected: DeletionOutcome| outcomes.iter()
+                .filter(|&&outcome| outcome == expected).count() as u64;
+            let stats = source.tree.stats();
+            assert_eq!(count(DeletionOutcome::InDegenerateState),
+                       stats.removals_in_degenerate_state);
+            assert_eq!(count(DeletionOutcome::PrefixRepeated),
+                       stats.removals_skipped_for_repeated_prefix);
+            assert_eq!(count(DeletionOutcome::RootToDegenerate),
+                       stats.removals_making_tree_degenerate);
+            assert_eq!(count(DeletionOutcome::RootChildPromoted),
+                       stats.removals_promoting_root_child);
+            assert_eq!(count(DeletionOutcome::NodeInActiveContexts),
+                       stats.removals_of_node_in_active_contexts);
+            assert_eq!(count(DeletionOutcome::NodeNotInActiveContexts),
+                       stats.removals_of_node_not_in_active_contexts);
+            assert_eq!(count(DeletionOutcome::RootToDegenerate), 1);
+        }
+    }
+}
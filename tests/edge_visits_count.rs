@@ -0,0 +1,94 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+use demixer::history::{
+    CollectedContextStates,
+    HistorySource,
+};
+use demixer::history::tree::{
+    Direction,
+    MAX_EDGE_VISITS_COUNT,
+    Tree,
+    TreeHistorySource,
+    TreeState,
+};
+
+fn max_node_edge_visits_count(tree: &Tree) -> usize {
+    let mut max_count = 0;
+    let mut stack = Vec::new();
+    if tree.tree_state == TreeState::Proper {
+        stack.push(tree.get_root_node_index());
+    }
+    while let Some(node_index) = stack.pop() {
+        let node = tree.nodes()[node_index];
+        max_count = max_count.max(node.left_count()).max(node.right_count());
+        for &direction in [Direction::Left, Direction::Right].iter() {
+            if node.child(direction).is_node_index() {
+                stack.push(node.child(direction).to_node_index());
+            }
+        }
+    }
+    max_count
+}
+
+fn check_counts_on_input(input: &[u8], max_window_size: usize,
+                         max_order: usize) -> usize {
+    let mut source = TreeHistorySource::new(max_window_size, max_order);
+    let mut results = CollectedContextStates::new(max_order);
+    let mut max_seen_count = 0;
+    for &byte in input.iter() {
+        source.start_new_byte();
+        for bit_index in (0..7 + 1).rev() {
+            source.gather_history_states(&mut results);
+            for context in source.active_contexts.items().iter() {
+                let count = context.incoming_edge_visits_count();
+                assert!(count <= MAX_EDGE_VISITS_COUNT);
+                max_seen_count = max_seen_count.max(count);
+            }
+            let node_count = max_node_edge_visits_count(&source.tree);
+            assert!(node_count <= MAX_EDGE_VISITS_COUNT);
+            max_seen_count = max_seen_count.max(node_count);
+            source.process_input_bit((byte & (1 << bit_index)) != 0);
+        }
+    }
+    max_seen_count
+}
+
+#[test]
+fn edge_visits_counts_are_capped_for_frequent_contexts() {
+    let mut input = b"ab".repeat(300);
+    input.extend_from_slice(&b"abc".repeat(100));
+    for &max_order in [0, 1, 3, 7].iter() {
+        assert_eq!(check_counts_on_input(&input, input.len(), max_order),
+                   MAX_EDGE_VISITS_COUNT);
+        assert_eq!(check_counts_on_input(&input, 50, max_order),
+                   MAX_EDGE_VISITS_COUNT);
+    }
+}
+
+#[test]
+fn edge_visits_counts_are_capped_after_long_runs() {
+    let mut input = vec![b'x'; 500];
+    input.push(b'y');
+    input.extend_from_slice(&[b'x'; 200]);
+    for &max_order in [0, 1, 3, 20].iter() {
+        assert_eq!(check_counts_on_input(&input, input.len(), max_order),
+                   MAX_EDGE_VISITS_COUNT);
+    }
+}
@@ -0,0 +1,94 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+use demixer::coding::FinalProbability;
+use demixer::coding::encoder::Encoder;
+use demixer::random::{MersenneTwister, Prng};
+
+fn encode(bits: &[(FinalProbability, bool)]) -> Vec<u8> {
+    let mut encoder = Encoder::new(Vec::new());
+    for &(probability, bit) in bits.iter() {
+        encoder.encode_bit(probability, bit).unwrap();
+    }
+    encoder.finish().unwrap()
+}
+
+fn cost_in_bytes(bits: &[(FinalProbability, bool)]) -> f64 {
+    bits.iter().map(|&(probability, bit)| {
+        let p = probability.as_f64();
+        -(if bit { 1.0 - p } else { p }).log2()
+    }).sum::<f64>() / 8.0
+}
+
+#[test]
+fn empty_input_gives_only_flushed_bytes() {
+    assert_eq!(encode(&[]), vec![0; 5]);
+}
+
+#[test]
+fn output_size_is_close_to_cost() {
+    let mut prng = MersenneTwister::default();
+    for &skew in [0.5, 0.9, 0.999].iter() {
+        let bits: Vec<_> = (0..100_000).map(|_| {
+            let bit = prng.next_real2() >= skew;
+            let raw = (skew * FinalProbability::ONE_RAW as f64) as u32;
+            (FinalProbability::new(raw), bit)
+        }).collect();
+        let output_size = encode(&bits).len() as f64;
+        let cost = cost_in_bytes(&bits);
+        assert!(output_size >= cost.floor(), "skew = {}", skew);
+        assert!(output_size <= cost * 1.001 + 6.0,
+                "skew = {}, size = {}, cost = {}", skew, output_size, cost);
+    }
+}
+
+#[test]
+fn handles_extreme_probabilities() {
+    let lowest = FinalProbability::new(1);
+    let highest = FinalProbability::new(FinalProbability::ONE_RAW - 1);
+    let bits: Vec<_> = (0..1000).map(|index| match index % 4 {
+        0 => (lowest, false),
+        1 => (lowest, true),
+        2 => (highest, false),
+        _ => (highest, true),
+    }).collect();
+    let output_size = encode(&bits).len() as f64;
+    assert!(output_size <= cost_in_bytes(&bits) * 1.01 + 6.0);
+}
+
+#[test]
+fn long_runs_of_likely_bits() {
+    let likely_one = FinalProbability::new(1000);
+    let mut bits = Vec::new();
+    for run in 0..200 {
+        for _ in 0..run * 10 {
+            bits.push((likely_one, true));
+        }
+        bits.push((likely_one, false));
+    }
+    let output = encode(&bits);
+    assert_eq!(output, encode(&bits));
+    assert!(output.len() as f64 <= cost_in_bytes(&bits) * 1.01 + 6.0);
+}
+
+#[test]
+#[should_panic(expected = "probability out of range")]
+fn rejects_certain_probability() {
+    FinalProbability::new(FinalProbability::ONE_RAW);
+}
@@ -0,0 +1,87 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+use demixer::history::{
+    CollectedContextStates,
+    HistorySource,
+};
+use demixer::history::fat_map::FatMapHistorySource;
+use demixer::history::tree::TreeHistorySource;
+use demixer::random::MersenneTwister;
+use demixer::util::hash::{
+    ContextHasher,
+    DefaultContextHasher,
+    MultiplyShiftContextHasher,
+};
+
+/** Returns number of hash collisions after verifying results against tree */
+fn compare_with_tree<H: ContextHasher>(input: &[u8], max_order: usize,
+                                       hasher: H) -> usize {
+    let mut fat_map_source =
+        FatMapHistorySource::new_with_hasher(input.len(), max_order, hasher);
+    let mut tree_source = TreeHistorySource::new(input.len(), max_order);
+    let mut fat_map_source_results = CollectedContextStates::new(max_order);
+    let mut tree_source_results = CollectedContextStates::new(max_order);
+    for (index, &byte) in input.iter().enumerate() {
+        fat_map_source.start_new_byte();
+        tree_source.start_new_byte();
+        for bit_index in (0..7 + 1).rev() {
+            fat_map_source_results.reset();
+            fat_map_source.gather_history_states(&mut fat_map_source_results);
+            tree_source_results.reset();
+            tree_source.gather_history_states(&mut tree_source_results);
+            assert_eq!(fat_map_source_results.items(),
+                       tree_source_results.items(),
+                       "index = {}, bit index = {}", index, bit_index);
+            let input_bit = (byte & (1 << bit_index)) != 0;
+            fat_map_source.process_input_bit(input_bit);
+            tree_source.process_input_bit(input_bit);
+        }
+    }
+    fat_map_source.hash_collisions_count()
+}
+
+#[test]
+fn narrow_hash_collides_more_but_results_stay_valid() {
+    let mut prng = MersenneTwister::default();
+    let input: Vec<u8> =
+        (0..1000).map(|_| (prng.next_int64() % 16) as u8).collect();
+    let max_order = 3;
+    let default_collisions =
+        compare_with_tree(&input, max_order, DefaultContextHasher);
+    let wide_collisions = compare_with_tree(
+        &input, max_order, MultiplyShiftContextHasher::default());
+    let narrow_collisions = compare_with_tree(
+        &input, max_order, MultiplyShiftContextHasher::new(6));
+    assert_eq!(default_collisions, 0);
+    assert_eq!(wide_collisions, 0);
+    assert!(narrow_collisions > 0);
+}
+
+#[test]
+fn hashers_are_deterministic() {
+    let context = b"deterministic";
+    for prefix in 1..256 {
+        assert_eq!(DefaultContextHasher.hash(context, prefix),
+                   DefaultContextHasher.hash(context, prefix));
+        let hasher = MultiplyShiftContextHasher::new(20);
+        assert_eq!(hasher.hash(context, prefix), hasher.hash(context, prefix));
+        assert!(hasher.hash(context, prefix) < 1 << 20);
+    }
+}
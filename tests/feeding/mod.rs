@@ -0,0 +1,46 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+#![allow(dead_code)]
+
+extern crate demixer;
+
+use demixer::history::HistorySource;
+use demixer::history::tree::TreeHistorySource;
+
+/** Feeds whole bytes, most significant bit first */
+pub fn feed<Source: HistorySource>(source: &mut Source, input: &[u8]) {
+    for &byte in input.iter() {
+        source.start_new_byte();
+        for bit_index in (0..7 + 1).rev() {
+            source.process_input_bit((byte & (1 << bit_index)) != 0);
+        }
+    }
+}
+
+/** Like feed, but checks tree integrity before every byte */
+pub fn feed_checked(source: &mut TreeHistorySource, input: &[u8],
+                    max_order: usize) {
+    for &byte in input.iter() {
+        source.active_contexts.check_integrity(&source.tree);
+        source.tree.check_integrity(max_order);
+        source.start_new_byte();
+        for bit_index in (0..7 + 1).rev() {
+            source.process_input_bit((byte & (1 << bit_index)) != 0);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+use std::io;
+
+use demixer::{MAX_ORDER, MAX_WINDOW_SIZE};
+use demixer::coding::Header;
+
+fn roundtrip(header: &Header) -> Vec<u8> {
+    let mut serialized = Vec::new();
+    header.write(&mut serialized).unwrap();
+    let mut input = &serialized[..];
+    assert_eq!(&Header::read(&mut input).unwrap(), header);
+    assert!(input.is_empty());
+    serialized
+}
+
+fn read_error_kind(serialized: &[u8]) -> io::ErrorKind {
+    Header::read(&mut &serialized[..]).unwrap_err().kind()
+}
+
+#[test]
+fn roundtrip_of_empty_file_header() {
+    let serialized = roundtrip(&Header {
+        max_order: 0,
+        max_window_size: 1,
+        degenerate_switch_threshold: 1,
+        length: 0,
+    });
+    assert_eq!(serialized, b"DMXR\x01\x00\x01\x01\x00");
+}
+
+#[test]
+fn roundtrip_of_large_values() {
+    for &length in [127, 128, 1 << 32, (1 << 32) + 1, u64::MAX].iter() {
+        roundtrip(&Header {
+            max_order: MAX_ORDER,
+            max_window_size: MAX_WINDOW_SIZE,
+            degenerate_switch_threshold: 1000,
+            length,
+        });
+    }
+}
+
+#[test]
+fn rejects_wrong_magic() {
+    assert_eq!(read_error_kind(b"DMXQ\x01\x00\x01\x00"),
+               io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn rejects_unsupported_version() {
+    let error =
+        Header::read(&mut &b"DMXR\x02\x00\x01\x01\x00"[..]).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    assert!(error.to_string().contains("unsupported format version 2"));
+}
+
+#[test]
+fn rejects_too_high_order() {
+    assert_eq!(read_error_kind(b"DMXR\x01\x40\x01\x01\x00"),
+               io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn rejects_zero_window() {
+    assert_eq!(read_error_kind(b"DMXR\x01\x00\x00\x01\x00"),
+               io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn rejects_order_not_below_window() {
+    assert_eq!(read_error_kind(b"DMXR\x01\x01\x01\x01\x00"),
+               io::ErrorKind::InvalidData);
+    assert_eq!(read_error_kind(b"DMXR\x01\x05\x03\x01\x00"),
+               io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn rejects_too_large_window() {
+    // LEB128 of MAX_WINDOW_SIZE + 1
+    assert_eq!(read_error_kind(b"DMXR\x01\x00\x81\x80\x80\x20\x01\x00"),
+               io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn rejects_threshold_out_of_range() {
+    assert_eq!(read_error_kind(b"DMXR\x01\x00\x05\x00\x00"),
+               io::ErrorKind::InvalidData);
+    assert_eq!(read_error_kind(b"DMXR\x01\x00\x05\x06\x00"),
+               io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn rejects_overlong_varint() {
+    let mut serialized = b"DMXR\x01\x00\x01\x01".to_vec();
+    serialized.extend_from_slice(&[0xFF; 9]);
+    serialized.push(0x02);
+    assert_eq!(read_error_kind(&serialized), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn rejects_truncated_header() {
+    let mut serialized = Vec::new();
+    Header {
+        max_order: 5,
+        max_window_size: 1 << 20,
+        degenerate_switch_threshold: 3,
+        length: 1 << 40,
+    }.write(&mut serialized).unwrap();
+    for length in 0..serialized.len() {
+        assert_eq!(read_error_kind(&serialized[..length]),
+                   io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,116 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+mod feeding;
+
+use feeding::feed;
+use demixer::history::{
+    CollectedContextStates,
+    HistorySource,
+};
+use demixer::history::fat_map::FatMapHistorySource;
+use demixer::history::tree::{Nodes, NodesAllocation, TreeHistorySource};
+use demixer::random::MersenneTwister;
+
+#[test]
+fn bump_only_allocation_builds_same_tree_for_whole_input() {
+    let input: Vec<u8> = b"abracadabra, abracadabra!".to_vec();
+    let mut free_list_source = TreeHistorySource::new_with_allocation(
+        input.len(), 7, NodesAllocation::FreeList);
+    let mut bump_only_source = TreeHistorySource::new_with_allocation(
+        input.len(), 7, NodesAllocation::BumpOnly);
+    feed(&mut free_list_source, &input);
+    feed(&mut bump_only_source, &input);
+    bump_only_source.tree.check_integrity(7);
+    assert_eq!(free_list_source.tree.nodes().live_nodes_count(),
+               bump_only_source.tree.nodes().live_nodes_count());
+}
+
+#[test]
+#[should_panic(expected = "window can't slide with bump only allocation")]
+fn bump_only_allocation_forbids_sliding() {
+    let input: Vec<u8> = b"abracadabra, abracadabra!".to_vec();
+    let mut source = TreeHistorySource::new_with_allocation(
+        input.len() / 2, 7, NodesAllocation::BumpOnly);
+    feed(&mut source, &input);
+}
+
+#[test]
+fn bump_only_allocation_allows_clearing() {
+    let input: Vec<u8> = b"abracadabra, abracadabra!".to_vec();
+    let mut source = TreeHistorySource::new_with_allocation(
+        input.len(), 7, NodesAllocation::BumpOnly);
+    feed(&mut source, &input);
+    source.clear();
+    assert_eq!(source.tree.window_size, 0);
+    assert_eq!(source.tree.nodes().live_nodes_count(), 0);
+    feed(&mut source, &input);
+    source.tree.check_integrity(7);
+}
+
+#[test]
+#[should_panic(expected = "can only shrink to empty window")]
+fn bump_only_allocation_forbids_partial_shrinking() {
+    let input: Vec<u8> = b"abracadabra, abracadabra!".to_vec();
+    let mut source = TreeHistorySource::new_with_allocation(
+        input.len(), 7, NodesAllocation::BumpOnly);
+    feed(&mut source, &input);
+    source.shrink_to(input.len() / 2);
+}
+
+#[test]
+fn nodes_arena_grows_up_to_limit() {
+    let mut prng = MersenneTwister::default();
+    let input: Vec<u8> =
+        (0..10000).map(|_| (prng.next_int64() % 64) as u8).collect();
+    let max_order = 2;
+    let mut tree_source = TreeHistorySource::new(input.len(), max_order);
+    let mut fat_map_source = FatMapHistorySource::new(input.len(), max_order);
+    let mut tree_source_results = CollectedContextStates::new(max_order);
+    let mut fat_map_source_results = CollectedContextStates::new(max_order);
+    let initial_capacity = tree_source.tree.nodes().capacity();
+    assert!(initial_capacity <= Nodes::DEFAULT_INITIAL_CAPACITY);
+    for &byte in input.iter() {
+        tree_source.start_new_byte();
+        fat_map_source.start_new_byte();
+        for bit_index in (0..7 + 1).rev() {
+            tree_source_results.reset();
+            tree_source.gather_history_states(&mut tree_source_results);
+            fat_map_source_results.reset();
+            fat_map_source.gather_history_states(&mut fat_map_source_results);
+            assert_eq!(tree_source_results.items(),
+                       fat_map_source_results.items());
+            let input_bit = (byte & (1 << bit_index)) != 0;
+            tree_source.process_input_bit(input_bit);
+            fat_map_source.process_input_bit(input_bit);
+        }
+    }
+    let nodes = tree_source.tree.nodes();
+    assert!(nodes.live_nodes_count() > initial_capacity);
+    assert!(nodes.capacity() > initial_capacity);
+    assert!(nodes.capacity() <= nodes.nodes_limit());
+}
+
+#[test]
+fn nodes_arena_capacity_never_exceeds_limit() {
+    let mut nodes = Nodes::new_with_capacity(3, 100, NodesAllocation::FreeList);
+    assert_eq!(nodes.capacity(), 3);
+    nodes = Nodes::new_with_capacity(3, 0, NodesAllocation::FreeList);
+    assert!(nodes.capacity() >= 1 && nodes.capacity() <= 3);
+}
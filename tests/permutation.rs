@@ -0,0 +1,65 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+use std::collections::HashMap;
+
+use demixer::random::{MersenneTwister, Prng, Xorshift128Plus};
+use demixer::util::permutation::random_permutation;
+
+#[test]
+fn shuffled_slice_sorts_back_to_original() {
+    let mut prng = MersenneTwister::default();
+    for &length in [0, 1, 2, 10, 1000].iter() {
+        let original: Vec<u32> = (0..length).map(|item| item * 3).collect();
+        let mut shuffled = original.clone();
+        prng.shuffle(&mut shuffled);
+        if length >= 10 {
+            assert_ne!(shuffled, original);
+        }
+        shuffled.sort();
+        assert_eq!(shuffled, original);
+    }
+}
+
+#[test]
+fn random_permutation_contains_each_index_once() {
+    let mut prng = Xorshift128Plus::default();
+    for &size in [0, 1, 5, 500].iter() {
+        let mut permutation = random_permutation(size, &mut prng);
+        assert_eq!(permutation.len(), size);
+        permutation.sort();
+        assert!(permutation.iter().enumerate()
+            .all(|(index, &item)| index == item));
+    }
+}
+
+#[test]
+fn all_orderings_are_roughly_equally_likely() {
+    let mut prng = MersenneTwister::default();
+    let mut counts = HashMap::new();
+    let draws = 60_000;
+    for _ in 0..draws {
+        *counts.entry(random_permutation(3, &mut prng)).or_insert(0) += 1;
+    }
+    assert_eq!(counts.len(), 6);
+    for (permutation, &count) in counts.iter() {
+        assert!((count as f64 - draws as f64 / 6.0).abs() < 500.0,
+                "permutation = {:?}, count = {}", permutation, count);
+    }
+}
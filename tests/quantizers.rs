@@ -0,0 +1,42 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+use demixer::util::quantizers::{DISTANCE_BUCKETS_COUNT, quantize_distance};
+
+#[test]
+fn distances_map_to_increasing_buckets() {
+    let buckets: Vec<u8> =
+        [1, 10, 1000].iter().map(|&d| quantize_distance(d)).collect();
+    assert!(buckets[0] < buckets[1]);
+    assert!(buckets[1] < buckets[2]);
+}
+
+#[test]
+fn distance_buckets_are_monotone_and_bounded() {
+    let mut previous_bucket = quantize_distance(0);
+    for shift in 0..64 {
+        for &distance in [(1usize << shift) - 1, 1 << shift].iter() {
+            let bucket = quantize_distance(distance);
+            assert!(bucket >= previous_bucket);
+            assert!((bucket as usize) < DISTANCE_BUCKETS_COUNT);
+            previous_bucket = bucket;
+        }
+    }
+    assert!((quantize_distance(usize::MAX) as usize) < DISTANCE_BUCKETS_COUNT);
+}
@@ -0,0 +1,164 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+use demixer::random::{MersenneTwister, Prng, Xorshift128Plus};
+
+#[test]
+fn mersenne_twister_matches_reference_outputs() {
+    let mut prng = MersenneTwister::new_by_array_seed(
+        &[0x12345, 0x23456, 0x34567, 0x45678]);
+    let expected: [u64; 5] = [
+        7266447313870364031, 4946485549665804864, 16945909448695747420,
+        16394063075524226720, 4873882236456199058,
+    ];
+    for &expected_output in expected.iter() {
+        assert_eq!(prng.next_int64(), expected_output);
+    }
+
+    let mut prng = MersenneTwister::default();
+    for _ in 0..9999 {
+        prng.next_int64();
+    }
+    assert_eq!(prng.next_int64(), 9981545732273789042);
+}
+
+#[test]
+fn mersenne_twister_real2_is_in_unit_interval() {
+    let mut prng = MersenneTwister::default();
+    for _ in 0..10000 {
+        let real = prng.next_real2();
+        assert!((0.0..1.0).contains(&real));
+    }
+}
+
+#[test]
+fn mersenne_twister_restored_from_state_continues_identically() {
+    for &skipped_outputs in [0, 1, 311, 312, 313, 500].iter() {
+        let mut prng = MersenneTwister::new_by_scalar_seed(12345);
+        for _ in 0..skipped_outputs {
+            prng.next_int64();
+        }
+        let (words, index) = prng.state();
+        let mut restored = MersenneTwister::from_state(words, index);
+        for _ in 0..1000 {
+            assert_eq!(restored.next_int64(), prng.next_int64());
+        }
+        assert_eq!(restored.state().1, prng.state().1);
+    }
+}
+
+#[test]
+fn xorshift128plus_matches_reference_outputs() {
+    let mut prng = Xorshift128Plus::from_state([1, 2]);
+    let expected: [u64; 5] =
+        [3, 8388645, 33816707, 70368778527840, 211106267172129];
+    for &expected_output in expected.iter() {
+        assert_eq!(prng.next_int64(), expected_output);
+    }
+
+    // state expanded with SplitMix64, its first output for seed 0 is a
+    // well known value
+    let state = Xorshift128Plus::new_by_scalar_seed(0).state();
+    assert_eq!(state[0], 0xE220_A839_7B1D_CDAF);
+
+    let mut prng = Xorshift128Plus::new_by_scalar_seed(12345);
+    let expected: [u64; 3] = [
+        6233086606872742541, 8068093359486558699, 13279572863143373870,
+    ];
+    for &expected_output in expected.iter() {
+        assert_eq!(prng.next_int64(), expected_output);
+    }
+    for _ in 3..999 {
+        prng.next_int64();
+    }
+    assert_eq!(prng.next_int64(), 853126557890749925);
+}
+
+#[test]
+#[should_panic(expected = "state must not be all zeros")]
+fn xorshift128plus_rejects_zero_state() {
+    Xorshift128Plus::from_state([0, 0]);
+}
+
+fn average_of_reals<P: Prng>(prng: &mut P) -> f64 {
+    let count = 100_000;
+    (0..count).map(|_| {
+        let real = prng.next_real2();
+        assert!((0.0..1.0).contains(&real));
+        real
+    }).sum::<f64>() / count as f64
+}
+
+#[test]
+fn generators_are_usable_through_common_trait() {
+    let mt_average = average_of_reals(&mut MersenneTwister::default());
+    let xorshift_average = average_of_reals(&mut Xorshift128Plus::default());
+    assert!((mt_average - 0.5).abs() < 0.01);
+    assert!((xorshift_average - 0.5).abs() < 0.01);
+}
+
+fn chi_square_of_next_below<P: Prng>(prng: &mut P, bound: u64) -> f64 {
+    let draws_per_bucket = 10_000;
+    let mut buckets = vec![0u64; bound as usize];
+    for _ in 0..bound * draws_per_bucket {
+        buckets[prng.next_below(bound) as usize] += 1;
+    }
+    buckets.iter().map(|&count| {
+        let difference = count as f64 - draws_per_bucket as f64;
+        difference * difference / draws_per_bucket as f64
+    }).sum()
+}
+
+#[test]
+fn next_below_is_roughly_uniform() {
+    // critical values of chi-square distribution for p = 0.001
+    for &(bound, critical_value) in [(2, 10.83), (6, 20.52), (10, 27.88)]
+        .iter() {
+        let mt_chi_square =
+            chi_square_of_next_below(&mut MersenneTwister::default(), bound);
+        assert!(mt_chi_square < critical_value,
+                "bound = {}, chi square = {}", bound, mt_chi_square);
+        let xorshift_chi_square =
+            chi_square_of_next_below(&mut Xorshift128Plus::default(), bound);
+        assert!(xorshift_chi_square < critical_value,
+                "bound = {}, chi square = {}", bound, xorshift_chi_square);
+    }
+}
+
+#[test]
+fn next_below_has_no_modulo_bias_for_huge_bounds() {
+    // with plain modulo, values below 2^62 would be drawn half of the time
+    let bound = 3 << 62;
+    let mut prng = MersenneTwister::default();
+    let draws = 100_000;
+    let low_values_count = (0..draws)
+        .filter(|_| prng.next_below(bound) < 1 << 62).count();
+    let low_values_fraction = low_values_count as f64 / draws as f64;
+    assert!((low_values_fraction - 1.0 / 3.0).abs() < 0.01,
+            "fraction = {}", low_values_fraction);
+}
+
+#[test]
+fn next_below_handles_extreme_bounds() {
+    let mut prng = MersenneTwister::default();
+    for _ in 0..1000 {
+        assert_eq!(prng.next_below(1), 0);
+        assert!(prng.next_below(u64::MAX) < u64::MAX);
+    }
+}
@@ -0,0 +1,174 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+use demixer::coding::FinalProbability;
+use demixer::coding::decoder::Decoder;
+use demixer::coding::encoder::Encoder;
+use demixer::random::{MersenneTwister, Prng};
+
+/// Stand-in for a real predictor: order 1 context with bit counts
+struct Order1Model {
+    counts: Vec<[u32; 2]>,
+    previous_byte: u8,
+    current_prefix: usize,
+}
+
+impl Order1Model {
+    fn new() -> Order1Model {
+        Order1Model {
+            counts: vec![[0, 0]; 256 * 256],
+            previous_byte: 0,
+            current_prefix: 1,
+        }
+    }
+
+    fn context(&self) -> usize {
+        ((self.previous_byte as usize) << 8) | self.current_prefix
+    }
+
+    fn predict(&self) -> FinalProbability {
+        let [zeros, ones] = self.counts[self.context()];
+        let one = FinalProbability::ONE_RAW as u64;
+        let raw =
+            ((zeros as u64 * 2 + 1) * one) / ((zeros + ones) as u64 * 2 + 2);
+        FinalProbability::new((raw as u32).max(1).min(one as u32 - 1))
+    }
+
+    fn update(&mut self, bit: bool) {
+        let context = self.context();
+        self.counts[context][bit as usize] += 1;
+        self.current_prefix = (self.current_prefix << 1) | bit as usize;
+        if self.current_prefix >= 256 {
+            self.previous_byte = self.current_prefix as u8;
+            self.current_prefix = 1;
+        }
+    }
+}
+
+fn compress(input: &[u8]) -> Vec<u8> {
+    let mut model = Order1Model::new();
+    let mut encoder = Encoder::new(Vec::new());
+    for &byte in input.iter() {
+        for bit_index in (0..7 + 1).rev() {
+            let bit = (byte & (1 << bit_index)) != 0;
+            encoder.encode_bit(model.predict(), bit).unwrap();
+            model.update(bit);
+        }
+    }
+    encoder.finish().unwrap()
+}
+
+fn decompress(compressed: &[u8], length: usize) -> Vec<u8> {
+    let mut model = Order1Model::new();
+    let mut decoder = Decoder::new(compressed).unwrap();
+    let mut output = Vec::with_capacity(length);
+    for _ in 0..length {
+        let mut byte = 0u8;
+        for _ in 0..8 {
+            let bit = decoder.decode_bit(model.predict()).unwrap();
+            model.update(bit);
+            byte = (byte << 1) | bit as u8;
+        }
+        output.push(byte);
+    }
+    assert!(decoder.into_inner().is_empty());
+    output
+}
+
+fn check_roundtrip(input: &[u8]) -> usize {
+    let compressed = compress(input);
+    assert_eq!(decompress(&compressed, input.len()), input);
+    compressed.len()
+}
+
+#[test]
+fn roundtrip_of_empty_input() {
+    assert_eq!(check_roundtrip(&[]), 5);
+}
+
+#[test]
+fn roundtrip_of_runs() {
+    for &byte in [0u8, 0xFF, b'a'].iter() {
+        let compressed_size = check_roundtrip(&[byte; 100_000]);
+        assert!(compressed_size < 100, "byte = {}", byte);
+    }
+}
+
+#[test]
+fn roundtrip_of_text() {
+    let text = b"It was the best of times, it was the worst of times, it was \
+        the age of wisdom, it was the age of foolishness, it was the epoch of \
+        belief, it was the epoch of incredulity, it was the season of Light, \
+        it was the season of Darkness, it was the spring of hope, it was the \
+        winter of despair.";
+    let input: Vec<u8> = text.iter().cycle().take(50_000).cloned().collect();
+    let compressed_size = check_roundtrip(&input);
+    assert!(compressed_size < input.len() / 2);
+}
+
+#[test]
+fn roundtrip_of_random_bytes() {
+    let mut prng = MersenneTwister::default();
+    for &alphabet_size in [2, 17, 256].iter() {
+        let input: Vec<u8> = (0..30_000)
+            .map(|_| (prng.next_int64() % alphabet_size) as u8).collect();
+        check_roundtrip(&input);
+    }
+}
+
+#[test]
+fn roundtrip_with_extreme_probabilities() {
+    let mut prng = MersenneTwister::default();
+    let probabilities: Vec<_> = (0..200_000).map(|_| {
+        match prng.next_int64() % 3 {
+            0 => FinalProbability::new(1),
+            1 => FinalProbability::new(FinalProbability::ONE_RAW - 1),
+            _ => FinalProbability::new(
+                1 + (prng.next_int64() % (FinalProbability::ONE_RAW as u64 - 1))
+                    as u32),
+        }
+    }).collect();
+    let bits: Vec<_> = probabilities.iter()
+        .map(|&probability| prng.next_real2() >= probability.as_f64())
+        .collect();
+    let mut encoder = Encoder::new(Vec::new());
+    for (&probability, &bit) in probabilities.iter().zip(bits.iter()) {
+        encoder.encode_bit(probability, bit).unwrap();
+    }
+    let compressed = encoder.finish().unwrap();
+    let mut decoder = Decoder::new(&compressed[..]).unwrap();
+    for (&probability, &bit) in probabilities.iter().zip(bits.iter()) {
+        assert_eq!(decoder.decode_bit(probability).unwrap(), bit);
+    }
+}
+
+#[test]
+fn truncated_stream_fails_to_decode() {
+    let input = b"truncated stream fails to decode".to_vec();
+    let compressed = compress(&input);
+    let truncated = &compressed[..compressed.len() / 2];
+    let mut model = Order1Model::new();
+    let mut decoder = Decoder::new(truncated).unwrap();
+    let result = (0..input.len() * 8).map(|_| {
+        let bit = decoder.decode_bit(model.predict())?;
+        model.update(bit);
+        Ok(bit)
+    }).collect::<Result<Vec<_>, std::io::Error>>();
+    assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,87 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+mod feeding;
+
+use feeding::feed_checked;
+use demixer::history::HistorySource;
+use demixer::history::tree::TreeHistorySource;
+
+/** Returns active contexts counts before and after removal if the leftmost
+ suffix removal was skipped because its prefix was repeated later */
+fn remove_leftmost_suffix(input: &[u8], max_order: usize)
+                          -> Option<(usize, usize)> {
+    let mut source = TreeHistorySource::new(input.len(), max_order);
+    feed_checked(&mut source, input, max_order);
+    let window_start = source.tree.window_cursor - source.tree.window_size;
+    let contexts_count_before = source.active_contexts.items().len();
+    let expected_contexts_count = source.active_contexts.items().iter()
+        .take_while(|ctx| ctx.suffix_index() != window_start).count();
+    assert!(source.active_contexts.items()[expected_contexts_count..].iter()
+        .all(|ctx| ctx.suffix_index() == window_start));
+    let skipped_removals_before =
+        source.tree.stats().removals_skipped_for_repeated_prefix;
+
+    source.tree.remove_leftmost_suffix(&mut source.active_contexts);
+
+    if source.tree.stats().removals_skipped_for_repeated_prefix ==
+        skipped_removals_before {
+        return None;
+    }
+    let contexts_count_after = source.active_contexts.items().len();
+    assert_eq!(contexts_count_after, expected_contexts_count,
+               "input = {:?}, max order = {}", input, max_order);
+    assert!(source.active_contexts.items().iter()
+        .all(|ctx| ctx.suffix_index() > window_start));
+    source.active_contexts.check_integrity(&source.tree);
+    source.tree.check_integrity(max_order);
+    feed_checked(&mut source, b"abba", max_order);
+    source.active_contexts.check_integrity(&source.tree);
+    source.tree.check_integrity(max_order);
+    Some((contexts_count_before, contexts_count_after))
+}
+
+#[test]
+fn repeated_prefix_removal_trims_active_contexts() {
+    assert_eq!(remove_leftmost_suffix(b"baba", 2), Some((2, 1)));
+    assert_eq!(remove_leftmost_suffix(b"aba", 1), Some((1, 0)));
+    assert_eq!(remove_leftmost_suffix(b"ab", 1), None);
+}
+
+#[test]
+fn repeated_prefix_removal_for_all_short_binary_inputs() {
+    let mut partial_trims = 0;
+    for length in 2..9 {
+        for code in 0..1u32 << length {
+            let input: Vec<u8> = (0..length)
+                .map(|i| if (code >> i) & 1 == 1 { b'b' } else { b'a' })
+                .collect();
+            for max_order in 0..length - 1 {
+                if let Some((before, after)) =
+                    remove_leftmost_suffix(&input, max_order) {
+                    assert!(after <= before);
+                    if after > 0 && after < before {
+                        partial_trims += 1;
+                    }
+                }
+            }
+        }
+    }
+    assert!(partial_trims > 0);
+}
@@ -0,0 +1,129 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+mod feeding;
+
+use feeding::feed_checked;
+use demixer::history::{
+    CollectedContextStates,
+    HistorySource,
+};
+use demixer::history::tree::{
+    TreeHistorySource,
+    TreeState,
+};
+
+fn make_input(length: usize) -> Vec<u8> {
+    let mut input = vec![b'a'];
+    let mut next_symbol = b'b';
+    while input.len() < length {
+        let mut clone = input.clone();
+        input.append(&mut clone);
+        input.push(next_symbol);
+        next_symbol += 1;
+    }
+    input.truncate(length);
+    input
+}
+
+#[test]
+fn shrinking_keeps_tree_consistent() {
+    let max_order = 5;
+    let input = make_input(100);
+    let mut source = TreeHistorySource::new(input.len(), max_order);
+    feed_checked(&mut source, &input, max_order);
+    for &window_size in [80, 30, 7, 1].iter() {
+        source.shrink_to(window_size);
+        assert_eq!(source.tree.window_size, window_size);
+        source.active_contexts.check_integrity(&source.tree);
+        source.tree.check_integrity(max_order);
+    }
+    source.shrink_to(5);
+    assert_eq!(source.tree.window_size, 1);
+}
+
+#[test]
+fn cleared_source_is_degenerate_and_reusable() {
+    let max_order = 5;
+    let input = make_input(100);
+    let mut source = TreeHistorySource::new(input.len() * 2, max_order);
+    feed_checked(&mut source, &input, max_order);
+    assert!(source.tree.nodes().live_nodes_count() > 0);
+
+    source.clear();
+    assert_eq!(source.tree.window_size, 0);
+    assert!(source.tree.tree_state == TreeState::Degenerate);
+    assert_eq!(source.tree.nodes().live_nodes_count(), 0);
+    assert!(source.active_contexts.items().is_empty());
+
+    let mut fresh_source =
+        TreeHistorySource::new(input.len() * 2, max_order);
+    assert_eq!(source.tree.nodes().capacity(),
+               fresh_source.tree.nodes().capacity());
+    let mut results = CollectedContextStates::new(max_order);
+    let mut fresh_results = CollectedContextStates::new(max_order);
+    for &byte in input.iter() {
+        source.start_new_byte();
+        fresh_source.start_new_byte();
+        for bit_index in (0..7 + 1).rev() {
+            source.gather_history_states(&mut results);
+            fresh_source.gather_history_states(&mut fresh_results);
+            assert_eq!(results.items(), fresh_results.items());
+            let input_bit = (byte & (1 << bit_index)) != 0;
+            source.process_input_bit(input_bit);
+            fresh_source.process_input_bit(input_bit);
+        }
+    }
+    assert_eq!(source.tree.nodes().live_nodes_count(),
+               fresh_source.tree.nodes().live_nodes_count());
+}
+
+#[test]
+fn degenerate_tree_after_shrinking_matches_fresh_one() {
+    let max_order = 5;
+    let mut source = TreeHistorySource::new(100, max_order);
+    feed_checked(&mut source, b"bca", max_order);
+    source.shrink_to(1);
+    assert!(source.tree.tree_state == TreeState::Degenerate);
+    let offset = 2;
+    let mut fresh_source = TreeHistorySource::new(100, max_order);
+    feed_checked(&mut fresh_source, b"a", max_order);
+    let mut results = CollectedContextStates::new(max_order);
+    let mut fresh_results = CollectedContextStates::new(max_order);
+    for &byte in b"aaabaaab".iter() {
+        source.start_new_byte();
+        fresh_source.start_new_byte();
+        for bit_index in (0..7 + 1).rev() {
+            source.gather_history_states(&mut results);
+            fresh_source.gather_history_states(&mut fresh_results);
+            assert_eq!(
+                results.items().iter()
+                    .map(|state| (state.last_occurrence_index - offset,
+                                  state.bit_history))
+                    .collect::<Vec<_>>(),
+                fresh_results.items().iter()
+                    .map(|state| (state.last_occurrence_index,
+                                  state.bit_history))
+                    .collect::<Vec<_>>());
+            let input_bit = (byte & (1 << bit_index)) != 0;
+            source.process_input_bit(input_bit);
+            fresh_source.process_input_bit(input_bit);
+        }
+    }
+}
@@ -0,0 +1,134 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+use std::mem;
+
+use demixer::estimators::SimpleCounter;
+use demixer::history::HistorySource;
+use demixer::history::tree::{
+    Node,
+    NodesAllocation,
+    TreeHistorySource,
+    TreeState,
+};
+use demixer::random::{MersenneTwister, Prng};
+
+#[test]
+fn stays_strictly_between_zero_and_one() {
+    let mut counter = SimpleCounter::new();
+    for _ in 0..10_000 {
+        counter.update(true);
+    }
+    assert!(counter.prediction() < u16::MAX);
+    assert!(counter.as_f64() > 0.999);
+    for _ in 0..10_000 {
+        counter.update(false);
+    }
+    assert!(counter.prediction() > 0);
+    assert!(counter.as_f64() < 0.001);
+}
+
+#[test]
+fn run_matches_repeated_updates() {
+    for &bit in [false, true].iter() {
+        let mut counter = SimpleCounter::new();
+        for run_length in 0..100 {
+            assert_eq!(SimpleCounter::after_run(run_length, bit), counter);
+            counter.update(bit);
+        }
+    }
+}
+
+#[test]
+fn steady_state_is_unbiased_on_biased_coin() {
+    let mut prng = MersenneTwister::default();
+    for &probability in [0.1, 0.5, 0.8, 0.97].iter() {
+        let mut counter = SimpleCounter::new();
+        let mut predictions_sum = 0.0;
+        let updates_count = 200_000;
+        for _ in 0..updates_count {
+            counter.update(prng.next_real2() < probability);
+            predictions_sum += counter.as_f64();
+        }
+        let average = predictions_sum / updates_count as f64;
+        assert!((average - probability).abs() < 0.005,
+                "probability = {}, average = {}", probability, average);
+    }
+}
+
+fn feed_repeated_pattern(source: &mut TreeHistorySource) {
+    for _ in 0..200 {
+        for &byte in b"aaab".iter() {
+            source.start_new_byte();
+            for bit_index in (0..7 + 1).rev() {
+                source.process_input_bit((byte & (1 << bit_index)) != 0);
+            }
+        }
+    }
+}
+
+#[test]
+fn tree_nodes_track_branch_frequencies() {
+    let mut source = TreeHistorySource::new_with_config(
+        1000, 3, NodesAllocation::FreeList, 1, true);
+    feed_repeated_pattern(&mut source);
+    // 'a' and 'b' differ only on second lowest bit, so the root node
+    // branches there and sees a one in every fourth byte
+    let root_index = source.tree.get_root_node_index();
+    assert_eq!(source.tree.nodes()[root_index].depth(), 6);
+    let prediction = source.tree.nodes().counter(root_index).unwrap().as_f64();
+    assert!(prediction > 0.15 && prediction < 0.35,
+            "prediction = {}", prediction);
+}
+
+#[test]
+fn tree_nodes_have_no_counters_by_default() {
+    let mut source = TreeHistorySource::new(1000, 3);
+    feed_repeated_pattern(&mut source);
+    let root_index = source.tree.get_root_node_index();
+    assert_eq!(source.tree.nodes().counter(root_index), None);
+    assert_eq!(mem::size_of::<Node>(), 20);
+}
+
+#[test]
+fn node_counters_survive_sliding_window() {
+    let mut prng = MersenneTwister::default();
+    let input: Vec<u8> =
+        (0..3000).map(|_| b'a' + (prng.next_int64() % 3) as u8).collect();
+    let max_order = 4;
+    let mut source = TreeHistorySource::new_with_config(
+        100, max_order, NodesAllocation::FreeList, 1, true);
+    let mut plain_source = TreeHistorySource::new(100, max_order);
+    for &byte in input.iter() {
+        source.start_new_byte();
+        plain_source.start_new_byte();
+        for bit_index in (0..7 + 1).rev() {
+            let input_bit = (byte & (1 << bit_index)) != 0;
+            source.process_input_bit(input_bit);
+            plain_source.process_input_bit(input_bit);
+        }
+        if source.tree.tree_state == TreeState::Proper {
+            let root_index = source.tree.get_root_node_index();
+            assert!(source.tree.nodes().counter(root_index).is_some());
+        }
+    }
+    source.tree.check_integrity(max_order);
+    assert_eq!(source.tree.nodes().live_nodes_count(),
+               plain_source.tree.nodes().live_nodes_count());
+}
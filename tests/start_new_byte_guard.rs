@@ -0,0 +1,74 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+use demixer::history::HistorySource;
+use demixer::history::naive::NaiveHistorySource;
+use demixer::history::fat_map::FatMapHistorySource;
+use demixer::history::tree::TreeHistorySource;
+
+fn start_after_partial_byte<Source: HistorySource>(bits_processed: usize) {
+    let mut source = Source::new(100, 5);
+    for byte in b"abc" {
+        source.start_new_byte();
+        for bit_index in (0..7 + 1).rev() {
+            source.process_input_bit((byte & (1 << bit_index)) != 0);
+        }
+    }
+    source.start_new_byte();
+    for _ in 0..bits_processed {
+        source.process_input_bit(true);
+    }
+    source.start_new_byte();
+}
+
+#[test]
+#[should_panic(expected = "start_new_byte called before all 8 bits")]
+fn naive_source_rejects_repeated_start() {
+    start_after_partial_byte::<NaiveHistorySource>(0);
+}
+
+#[test]
+#[should_panic(expected = "start_new_byte called before all 8 bits")]
+fn naive_source_rejects_start_after_partial_byte() {
+    start_after_partial_byte::<NaiveHistorySource>(3);
+}
+
+#[test]
+#[should_panic(expected = "start_new_byte called before all 8 bits")]
+fn fat_map_source_rejects_repeated_start() {
+    start_after_partial_byte::<FatMapHistorySource>(0);
+}
+
+#[test]
+#[should_panic(expected = "start_new_byte called before all 8 bits")]
+fn fat_map_source_rejects_start_after_partial_byte() {
+    start_after_partial_byte::<FatMapHistorySource>(3);
+}
+
+#[test]
+#[should_panic(expected = "start_new_byte called before all 8 bits")]
+fn tree_source_rejects_repeated_start() {
+    start_after_partial_byte::<TreeHistorySource>(0);
+}
+
+#[test]
+#[should_panic(expected = "start_new_byte called before all 8 bits")]
+fn tree_source_rejects_start_after_partial_byte() {
+    start_after_partial_byte::<TreeHistorySource>(3);
+}
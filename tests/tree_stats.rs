@@ -0,0 +1,92 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+mod feeding;
+
+use feeding::feed;
+use demixer::history::HistorySource;
+use demixer::history::tree::{TreeHistorySource, TreeStats};
+use demixer::random::{MersenneTwister, Prng};
+
+fn make_diverse_input<P: Prng>(prng: &mut P, length: usize) -> Vec<u8> {
+    let mut input = b"ab".to_vec();
+    while input.len() < length {
+        let choice = prng.next_int64() % 4;
+        if choice == 0 && input.len() > 10 {
+            let start = (prng.next_int64() as usize) % (input.len() - 10);
+            let repeated = input[start..start + 10].to_vec();
+            input.extend_from_slice(&repeated);
+        } else if choice == 1 {
+            input.extend_from_slice(&[b'z'; 5]);
+        } else {
+            input.push(b'a' + (prng.next_int64() % 6) as u8);
+        }
+    }
+    input.truncate(length);
+    input
+}
+
+fn total_removals(stats: &TreeStats) -> u64 {
+    stats.removals_in_degenerate_state +
+        stats.removals_skipped_for_repeated_prefix +
+        stats.removals_promoting_root_child +
+        stats.removals_making_tree_degenerate +
+        stats.removals_of_node_in_active_contexts +
+        stats.removals_of_node_not_in_active_contexts
+}
+
+#[test]
+fn splits_account_for_all_nodes_without_sliding() {
+    let input = make_diverse_input(&mut MersenneTwister::default(), 2000);
+    for &max_order in [0, 1, 3, 7, 20].iter() {
+        let mut source = TreeHistorySource::new(input.len(), max_order);
+        feed(&mut source, &input);
+        let stats = source.tree.stats();
+        assert!(stats.descends > 0);
+        assert!(stats.edge_splits > 0);
+        assert_eq!(stats.degenerate_root_edge_splits, 1);
+        assert_eq!(total_removals(stats), 0);
+        // input starts with two different symbols, so splitting degenerate
+        // root edge creates only the root node
+        assert_eq!(source.tree.nodes().live_nodes_count() as u64,
+                   stats.edge_splits + 1);
+    }
+}
+
+#[test]
+fn removals_are_counted_when_sliding() {
+    let input = make_diverse_input(&mut MersenneTwister::default(), 2000);
+    for &(max_window_size, max_order) in
+        [(1, 0), (2, 1), (50, 3), (300, 7), (500, 20)].iter() {
+        let mut source = TreeHistorySource::new(max_window_size, max_order);
+        feed(&mut source, &input);
+        let stats = source.tree.stats().clone();
+        assert_eq!(total_removals(&stats),
+                   (input.len() - max_window_size) as u64);
+        if max_window_size > 2 {
+            assert!(stats.removals_skipped_for_repeated_prefix > 0);
+            assert!(stats.removals_of_node_in_active_contexts +
+                stats.removals_of_node_not_in_active_contexts > 0);
+        }
+        source.shrink_to(0);
+        assert_eq!(total_removals(source.tree.stats()), input.len() as u64);
+        source.clear();
+        assert_eq!(source.tree.stats(), &TreeStats::default());
+    }
+}
@@ -0,0 +1,49 @@
+/*
+ *  demixer - file compressor aimed at high compression ratios
+ *  Copyright (C) 2018  Piotr Tarsa ( https://github.com/tarsa )
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+extern crate demixer;
+
+mod feeding;
+
+use feeding::feed;
+use demixer::history::HistorySource;
+use demixer::history::tree::{Tree, TreeHistorySource};
+
+#[test]
+fn degenerate_tree_gives_empty_graph() {
+    let mut source = TreeHistorySource::new(10, 5);
+    feed(&mut source, b"aaaa");
+    assert_eq!(source.tree.to_dot(), "digraph tree {\n}\n");
+}
+
+#[test]
+fn declares_every_node_and_leaf() {
+    let input = b"abacab";
+    let max_order = 5;
+    let mut source = TreeHistorySource::new(input.len(), max_order);
+    feed(&mut source, input);
+    let dot = source.tree.to_dot();
+    let nodes_count = Tree::count_distinct_suffixes(input, max_order);
+    let declarations = |prefix: &str| dot.lines()
+        .filter(|line| line.trim_start().starts_with(prefix) &&
+            !line.contains("->")).count();
+    assert_eq!(declarations("node"), nodes_count);
+    assert_eq!(declarations("leaf"), nodes_count + 1);
+    assert_eq!(dot.matches("->").count(), nodes_count * 2);
+    assert!(dot.starts_with("digraph tree {\n"));
+    assert!(dot.ends_with("}\n"));
+}